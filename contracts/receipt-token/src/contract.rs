@@ -0,0 +1,457 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    to_json_binary,
+    Addr,
+    Binary,
+    BlockInfo,
+    CosmosMsg,
+    Deps,
+    DepsMut,
+    Env,
+    MessageInfo,
+    Response,
+    StdResult,
+    Storage,
+    Uint128,
+    WasmMsg,
+};
+use cw2::set_contract_version;
+use cw20::{
+    AllowanceResponse,
+    BalanceResponse,
+    Cw20ReceiveMsg,
+    MinterResponse,
+    TokenInfoResponse,
+};
+use cw_utils::Expiration;
+
+use crate::error::ContractError;
+use crate::msg::{ ExecuteMsg, InstantiateMsg, QueryMsg, ReceiptHookMsg };
+use crate::state::{ ALLOWANCES, BALANCES, STAKING_CONTRACT, TOKEN_INFO };
+
+// version info for migration info
+const CONTRACT_NAME: &str = "injex-receipt-token";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg
+) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    TOKEN_INFO.save(
+        deps.storage,
+        &(TokenInfoResponse {
+            name: msg.name,
+            symbol: msg.symbol,
+            decimals: msg.decimals,
+            total_supply: Uint128::zero(),
+        })
+    )?;
+    STAKING_CONTRACT.save(deps.storage, &info.sender)?;
+
+    Ok(
+        Response::new()
+            .add_attribute("method", "instantiate")
+            .add_attribute("staking_contract", info.sender)
+    )
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::Transfer { recipient, amount } => transfer(deps, info, recipient, amount),
+        ExecuteMsg::Send { contract, amount, msg } => send(deps, info, contract, amount, msg),
+        ExecuteMsg::IncreaseAllowance { spender, amount, expires } =>
+            increase_allowance(deps, env, info, spender, amount, expires),
+        ExecuteMsg::DecreaseAllowance { spender, amount, expires } =>
+            decrease_allowance(deps, env, info, spender, amount, expires),
+        ExecuteMsg::TransferFrom { owner, recipient, amount } =>
+            transfer_from(deps, env, info, owner, recipient, amount),
+        ExecuteMsg::SendFrom { owner, contract, amount, msg } =>
+            send_from(deps, env, info, owner, contract, amount, msg),
+        ExecuteMsg::Mint { recipient, amount } => mint(deps, info, recipient, amount),
+        ExecuteMsg::Burn { amount } => burn(deps, info, amount),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Balance { address } => to_json_binary(&query_balance(deps, address)?),
+        QueryMsg::TokenInfo {} => to_json_binary(&query_token_info(deps)?),
+        QueryMsg::Minter {} => to_json_binary(&query_minter(deps)?),
+        QueryMsg::Allowance { owner, spender } =>
+            to_json_binary(&query_allowance(deps, owner, spender)?),
+        QueryMsg::StakingContract {} => to_json_binary(&query_staking_contract(deps)?),
+    }
+}
+
+pub fn transfer(
+    deps: DepsMut,
+    info: MessageInfo,
+    recipient: String,
+    amount: Uint128
+) -> Result<Response, ContractError> {
+    let recipient = deps.api.addr_validate(&recipient)?;
+
+    ensure_not_staking_contract(deps.as_ref(), &recipient)?;
+
+    let hook = move_balance(deps.storage, &info.sender, &recipient, amount)?;
+
+    Ok(
+        Response::new()
+            .add_message(hook)
+            .add_attribute("from", info.sender)
+            .add_attribute("to", recipient)
+            .add_attribute("amount", amount)
+            .add_attribute("method", "execute_transfer")
+    )
+}
+
+pub fn send(
+    deps: DepsMut,
+    info: MessageInfo,
+    contract: String,
+    amount: Uint128,
+    msg: Binary
+) -> Result<Response, ContractError> {
+    let contract = deps.api.addr_validate(&contract)?;
+    let hook = move_balance(deps.storage, &info.sender, &contract, amount)?;
+
+    let receive_msg = (Cw20ReceiveMsg {
+        sender: info.sender.to_string(),
+        amount,
+        msg,
+    }).into_cosmos_msg(contract.clone())?;
+
+    Ok(
+        Response::new()
+            .add_message(hook)
+            .add_message(receive_msg)
+            .add_attribute("from", info.sender)
+            .add_attribute("to", contract)
+            .add_attribute("amount", amount)
+            .add_attribute("method", "execute_send")
+    )
+}
+
+pub fn increase_allowance(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    spender: String,
+    amount: Uint128,
+    expires: Option<Expiration>
+) -> Result<Response, ContractError> {
+    let spender = deps.api.addr_validate(&spender)?;
+
+    if spender == info.sender {
+        return Err(ContractError::CannotSetOwnAccount {});
+    }
+
+    let key = (info.sender.clone(), spender.clone());
+    let mut allowance = ALLOWANCES.may_load(deps.storage, key.clone())?.unwrap_or_default();
+
+    if let Some(expires) = expires {
+        if expires.is_expired(&env.block) {
+            return Err(ContractError::InvalidExpiration {});
+        }
+
+        allowance.expires = expires;
+    }
+
+    allowance.allowance = allowance.allowance.checked_add(amount)?;
+
+    ALLOWANCES.save(deps.storage, key, &allowance)?;
+
+    Ok(
+        Response::new()
+            .add_attribute("owner", info.sender)
+            .add_attribute("spender", spender)
+            .add_attribute("amount", amount)
+            .add_attribute("method", "execute_increase_allowance")
+    )
+}
+
+pub fn decrease_allowance(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    spender: String,
+    amount: Uint128,
+    expires: Option<Expiration>
+) -> Result<Response, ContractError> {
+    let spender = deps.api.addr_validate(&spender)?;
+
+    if spender == info.sender {
+        return Err(ContractError::CannotSetOwnAccount {});
+    }
+
+    let key = (info.sender.clone(), spender.clone());
+    let mut allowance = ALLOWANCES.load(deps.storage, key.clone()).map_err(|_| {
+        ContractError::NoAllowance {}
+    })?;
+
+    // decreasing past zero drops the allowance
+    if amount >= allowance.allowance {
+        ALLOWANCES.remove(deps.storage, key);
+    } else {
+        if let Some(expires) = expires {
+            if expires.is_expired(&env.block) {
+                return Err(ContractError::InvalidExpiration {});
+            }
+
+            allowance.expires = expires;
+        }
+
+        allowance.allowance -= amount;
+
+        ALLOWANCES.save(deps.storage, key, &allowance)?;
+    }
+
+    Ok(
+        Response::new()
+            .add_attribute("owner", info.sender)
+            .add_attribute("spender", spender)
+            .add_attribute("amount", amount)
+            .add_attribute("method", "execute_decrease_allowance")
+    )
+}
+
+pub fn transfer_from(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner: String,
+    recipient: String,
+    amount: Uint128
+) -> Result<Response, ContractError> {
+    let owner = deps.api.addr_validate(&owner)?;
+    let recipient = deps.api.addr_validate(&recipient)?;
+
+    ensure_not_staking_contract(deps.as_ref(), &recipient)?;
+
+    spend_allowance(deps.storage, &owner, &info.sender, &env.block, amount)?;
+
+    let hook = move_balance(deps.storage, &owner, &recipient, amount)?;
+
+    Ok(
+        Response::new()
+            .add_message(hook)
+            .add_attribute("from", owner)
+            .add_attribute("to", recipient)
+            .add_attribute("by", info.sender)
+            .add_attribute("amount", amount)
+            .add_attribute("method", "execute_transfer_from")
+    )
+}
+
+pub fn send_from(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner: String,
+    contract: String,
+    amount: Uint128,
+    msg: Binary
+) -> Result<Response, ContractError> {
+    let owner = deps.api.addr_validate(&owner)?;
+    let contract = deps.api.addr_validate(&contract)?;
+
+    spend_allowance(deps.storage, &owner, &info.sender, &env.block, amount)?;
+
+    let hook = move_balance(deps.storage, &owner, &contract, amount)?;
+
+    let receive_msg = (Cw20ReceiveMsg {
+        sender: info.sender.to_string(),
+        amount,
+        msg,
+    }).into_cosmos_msg(contract.clone())?;
+
+    Ok(
+        Response::new()
+            .add_message(hook)
+            .add_message(receive_msg)
+            .add_attribute("from", owner)
+            .add_attribute("to", contract)
+            .add_attribute("by", info.sender)
+            .add_attribute("amount", amount)
+            .add_attribute("method", "execute_send_from")
+    )
+}
+
+/// Receipts are only minted against new stake
+pub fn mint(
+    deps: DepsMut,
+    info: MessageInfo,
+    recipient: String,
+    amount: Uint128
+) -> Result<Response, ContractError> {
+    ensure_staking_contract(deps.as_ref(), &info.sender)?;
+
+    if amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    let recipient = deps.api.addr_validate(&recipient)?;
+
+    BALANCES.update(deps.storage, recipient.clone(), |balance| -> StdResult<Uint128> {
+        Ok(balance.unwrap_or_default().checked_add(amount)?)
+    })?;
+    TOKEN_INFO.update(deps.storage, |mut info| -> StdResult<TokenInfoResponse> {
+        info.total_supply = info.total_supply.checked_add(amount)?;
+
+        Ok(info)
+    })?;
+
+    Ok(
+        Response::new()
+            .add_attribute("to", recipient)
+            .add_attribute("amount", amount)
+            .add_attribute("method", "execute_mint")
+    )
+}
+
+/// Receipts are only burned once the staking contract took them back against an unstake
+pub fn burn(deps: DepsMut, info: MessageInfo, amount: Uint128) -> Result<Response, ContractError> {
+    ensure_staking_contract(deps.as_ref(), &info.sender)?;
+
+    if amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    BALANCES.update(deps.storage, info.sender.clone(), |balance| -> StdResult<Uint128> {
+        Ok(balance.unwrap_or_default().checked_sub(amount)?)
+    })?;
+    TOKEN_INFO.update(deps.storage, |mut info| -> StdResult<TokenInfoResponse> {
+        info.total_supply = info.total_supply.checked_sub(amount)?;
+
+        Ok(info)
+    })?;
+
+    Ok(
+        Response::new()
+            .add_attribute("amount", amount)
+            .add_attribute("method", "execute_burn")
+    )
+}
+
+fn ensure_staking_contract(deps: Deps, sender: &Addr) -> Result<(), ContractError> {
+    if STAKING_CONTRACT.load(deps.storage)? != *sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    Ok(())
+}
+
+/// Receipts reaching the staking contract without a `Receive` would be stranded there
+fn ensure_not_staking_contract(deps: Deps, recipient: &Addr) -> Result<(), ContractError> {
+    if STAKING_CONTRACT.load(deps.storage)? == *recipient {
+        return Err(ContractError::StakingContractRecipient {});
+    }
+
+    Ok(())
+}
+
+/// Moves `amount` between two holders, the returned `ReceiptHookMsg` moves the stake with it
+fn move_balance(
+    storage: &mut dyn Storage,
+    from: &Addr,
+    to: &Addr,
+    amount: Uint128
+) -> Result<CosmosMsg, ContractError> {
+    if amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    BALANCES.update(storage, from.clone(), |balance| -> StdResult<Uint128> {
+        Ok(balance.unwrap_or_default().checked_sub(amount)?)
+    })?;
+    BALANCES.update(storage, to.clone(), |balance| -> StdResult<Uint128> {
+        Ok(balance.unwrap_or_default().checked_add(amount)?)
+    })?;
+
+    let hook = ReceiptHookMsg::ReceiptTransfer {
+        from: from.to_string(),
+        to: to.to_string(),
+        amount,
+    };
+
+    Ok(
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: STAKING_CONTRACT.load(storage)?.to_string(),
+            msg: to_json_binary(&hook)?,
+            funds: vec![],
+        })
+    )
+}
+
+/// Takes `amount` off what `owner` allowed `spender`
+fn spend_allowance(
+    storage: &mut dyn Storage,
+    owner: &Addr,
+    spender: &Addr,
+    block: &BlockInfo,
+    amount: Uint128
+) -> Result<(), ContractError> {
+    let key = (owner.clone(), spender.clone());
+    let mut allowance = ALLOWANCES.may_load(storage, key.clone())?.ok_or(
+        ContractError::NoAllowance {}
+    )?;
+
+    if allowance.expires.is_expired(block) {
+        return Err(ContractError::Expired {});
+    }
+
+    allowance.allowance = allowance.allowance.checked_sub(amount)?;
+
+    ALLOWANCES.save(storage, key, &allowance)?;
+
+    Ok(())
+}
+
+pub fn query_balance(deps: Deps, address: String) -> StdResult<BalanceResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let balance = BALANCES.may_load(deps.storage, address)?.unwrap_or_default();
+
+    Ok(BalanceResponse { balance })
+}
+
+pub fn query_token_info(deps: Deps) -> StdResult<TokenInfoResponse> {
+    let info = TOKEN_INFO.load(deps.storage)?;
+
+    Ok(info)
+}
+
+/// The staking contract mints without a cap, receipts follow the stake
+pub fn query_minter(deps: Deps) -> StdResult<MinterResponse> {
+    let minter = STAKING_CONTRACT.load(deps.storage)?;
+
+    Ok(MinterResponse {
+        minter: minter.to_string(),
+        cap: None,
+    })
+}
+
+pub fn query_allowance(deps: Deps, owner: String, spender: String) -> StdResult<AllowanceResponse> {
+    let owner = deps.api.addr_validate(&owner)?;
+    let spender = deps.api.addr_validate(&spender)?;
+    let allowance = ALLOWANCES.may_load(deps.storage, (owner, spender))?.unwrap_or_default();
+
+    Ok(allowance)
+}
+
+pub fn query_staking_contract(deps: Deps) -> StdResult<Addr> {
+    let staking_contract = STAKING_CONTRACT.load(deps.storage)?;
+
+    Ok(staking_contract)
+}
@@ -0,0 +1,23 @@
+use cosmwasm_std::{ OverflowError, StdError };
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ContractError {
+    #[error("{0}")] Std(#[from] StdError),
+
+    #[error("{0}")] Overflow(#[from] OverflowError),
+
+    #[error("Unauthorized")] Unauthorized {},
+
+    #[error("Invalid zero amount")] InvalidZeroAmount {},
+
+    #[error("Cannot set an allowance to own account")] CannotSetOwnAccount {},
+
+    #[error("No allowance for this account")] NoAllowance {},
+
+    #[error("Allowance is expired")] Expired {},
+
+    #[error("Invalid expiration value")] InvalidExpiration {},
+
+    #[error("Receipts go back to the staking contract through Send")] StakingContractRecipient {},
+}
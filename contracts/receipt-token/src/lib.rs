@@ -0,0 +1,7 @@
+pub mod contract;
+mod error;
+pub mod msg;
+pub mod state;
+mod test;
+
+pub use crate::error::ContractError;
@@ -0,0 +1,85 @@
+use cosmwasm_std::{ Binary, Uint128 };
+use cosmwasm_schema::cw_serde;
+use cw_utils::Expiration;
+
+/// The instantiating staking contract becomes the minter and is told about every transfer
+#[cw_serde]
+pub struct InstantiateMsg {
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+}
+
+/// The CW20 execute messages, without the marketing extension and burning from allowances
+#[cw_serde]
+pub enum ExecuteMsg {
+    Transfer {
+        recipient: String,
+        amount: Uint128,
+    },
+    Send {
+        contract: String,
+        amount: Uint128,
+        msg: Binary,
+    },
+    IncreaseAllowance {
+        spender: String,
+        amount: Uint128,
+        expires: Option<Expiration>,
+    },
+    DecreaseAllowance {
+        spender: String,
+        amount: Uint128,
+        expires: Option<Expiration>,
+    },
+    TransferFrom {
+        owner: String,
+        recipient: String,
+        amount: Uint128,
+    },
+    SendFrom {
+        owner: String,
+        contract: String,
+        amount: Uint128,
+        msg: Binary,
+    },
+    /// staking contract only
+    Mint {
+        recipient: String,
+        amount: Uint128,
+    },
+    /// staking contract only, burns receipts it was sent to unstake
+    Burn {
+        amount: Uint128,
+    },
+}
+
+#[cw_serde]
+pub enum QueryMsg {
+    /// returns `cw20::BalanceResponse`
+    Balance {
+        address: String,
+    },
+    /// returns `cw20::TokenInfoResponse`
+    TokenInfo {},
+    /// returns `cw20::MinterResponse`
+    Minter {},
+    /// returns `cw20::AllowanceResponse`
+    Allowance {
+        owner: String,
+        spender: String,
+    },
+    /// staking contract every transfer is reported to, lets it check it instantiated this code
+    StakingContract {},
+}
+
+/// Sent to the staking contract before the balances of two holders change, so the staked
+/// position moves along with its receipts
+#[cw_serde]
+pub enum ReceiptHookMsg {
+    ReceiptTransfer {
+        from: String,
+        to: String,
+        amount: Uint128,
+    },
+}
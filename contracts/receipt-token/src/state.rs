@@ -0,0 +1,13 @@
+use cosmwasm_std::{ Addr, Uint128 };
+use cw20::{ AllowanceResponse, TokenInfoResponse };
+use cw_storage_plus::{ Item, Map };
+
+pub const TOKEN_INFO: Item<TokenInfoResponse> = Item::new("token_info");
+
+/// minter of the token and receiver of every `ReceiptHookMsg`
+pub const STAKING_CONTRACT: Item<Addr> = Item::new("staking_contract");
+
+pub const BALANCES: Map<Addr, Uint128> = Map::new("balances");
+
+/// keyed by `(owner, spender)`
+pub const ALLOWANCES: Map<(Addr, Addr), AllowanceResponse> = Map::new("allowances");
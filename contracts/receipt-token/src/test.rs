@@ -0,0 +1,295 @@
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::{
+        to_json_binary,
+        Addr,
+        Binary,
+        Deps,
+        DepsMut,
+        Empty,
+        Env,
+        MessageInfo,
+        Response,
+        StdResult,
+        Uint128,
+    };
+    use cw20::{ AllowanceResponse, BalanceResponse, TokenInfoResponse };
+    use cw_multi_test::{ App, AppResponse, Contract, ContractWrapper, Executor };
+    use cw_storage_plus::Item;
+    use cw_utils::Expiration;
+
+    use crate::msg::{ ExecuteMsg, InstantiateMsg, QueryMsg, ReceiptHookMsg };
+
+    const HOLDER: &str = "inj1mvnlejy385wy498z7hvmflrsdfludx8ztxsx7m";
+    const SPENDER: &str = "inj12vpajtjf5cvmk2w737m0t8qwwkyjz0xgvxwyus";
+
+    // every `ReceiptTransfer` the mock staking contract received
+    const RECEIVED_HOOKS: Item<Vec<ReceiptHookMsg>> = Item::new("received_hooks");
+
+    fn token_template() -> Box<dyn Contract<Empty>> {
+        let contract = ContractWrapper::new(
+            crate::contract::execute,
+            crate::contract::instantiate,
+            crate::contract::query
+        );
+        Box::new(contract)
+    }
+
+    /// Stand-in for the staking contract that records the hooks it is sent
+    fn staking_template() -> Box<dyn Contract<Empty>> {
+        let contract = ContractWrapper::new(staking_execute, staking_instantiate, staking_query);
+        Box::new(contract)
+    }
+
+    fn staking_instantiate(
+        deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        _msg: Empty
+    ) -> StdResult<Response> {
+        RECEIVED_HOOKS.save(deps.storage, &vec![])?;
+
+        Ok(Response::new())
+    }
+
+    fn staking_execute(
+        deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        msg: ReceiptHookMsg
+    ) -> StdResult<Response> {
+        RECEIVED_HOOKS.update(deps.storage, |mut hooks| -> StdResult<_> {
+            hooks.push(msg);
+
+            Ok(hooks)
+        })?;
+
+        Ok(Response::new())
+    }
+
+    fn staking_query(deps: Deps, _env: Env, _msg: Empty) -> StdResult<Binary> {
+        to_json_binary(&RECEIVED_HOOKS.load(deps.storage)?)
+    }
+
+    fn proper_instantiate() -> (App, Addr, Addr) {
+        let mut app = App::default();
+        let staking_id = app.store_code(staking_template());
+        let token_id = app.store_code(token_template());
+
+        let staking = app
+            .instantiate_contract(
+                staking_id,
+                Addr::unchecked(HOLDER),
+                &Empty {},
+                &[],
+                "staking",
+                None
+            )
+            .unwrap();
+
+        let msg = InstantiateMsg {
+            name: "Staked Injex".to_string(),
+            symbol: "sINJX".to_string(),
+            decimals: 18,
+        };
+
+        // the staking contract instantiates its own receipt token
+        let token = app
+            .instantiate_contract(token_id, staking.clone(), &msg, &[], "receipt token", None)
+            .unwrap();
+
+        (app, staking, token)
+    }
+
+    fn expect_error(res: Result<AppResponse, anyhow::Error>, message: &str) {
+        assert_eq!(res.unwrap_err().root_cause().to_string(), message);
+    }
+
+    fn query_balance(app: &App, token: &Addr, address: &str) -> Uint128 {
+        let res: BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(token, &(QueryMsg::Balance { address: address.to_string() }))
+            .unwrap();
+
+        res.balance
+    }
+
+    #[test]
+    fn only_staking_contract_mints_and_burns() {
+        let (mut app, staking, token) = proper_instantiate();
+
+        let mint_msg = ExecuteMsg::Mint {
+            recipient: HOLDER.to_string(),
+            amount: Uint128::new(100),
+        };
+        let res = app.execute_contract(Addr::unchecked(HOLDER), token.clone(), &mint_msg, &[]);
+
+        expect_error(res, "Unauthorized");
+
+        app.execute_contract(staking.clone(), token.clone(), &mint_msg, &[]).unwrap();
+
+        let staking_contract: Addr = app
+            .wrap()
+            .query_wasm_smart(&token, &QueryMsg::StakingContract {})
+            .unwrap();
+
+        assert_eq!(staking_contract, staking);
+        assert_eq!(query_balance(&app, &token, HOLDER), Uint128::new(100));
+
+        let burn_msg = ExecuteMsg::Burn { amount: Uint128::new(40) };
+        let res = app.execute_contract(Addr::unchecked(HOLDER), token.clone(), &burn_msg, &[]);
+
+        expect_error(res, "Unauthorized");
+
+        // receipts only reach the staking contract with a `Receive` to settle them
+        let res = app.execute_contract(
+            Addr::unchecked(HOLDER),
+            token.clone(),
+            &(ExecuteMsg::Transfer {
+                recipient: staking.to_string(),
+                amount: Uint128::new(40),
+            }),
+            &[]
+        );
+
+        expect_error(res, "Receipts go back to the staking contract through Send");
+
+        app.execute_contract(
+            Addr::unchecked(HOLDER),
+            token.clone(),
+            &(ExecuteMsg::IncreaseAllowance {
+                spender: SPENDER.to_string(),
+                amount: Uint128::new(40),
+                expires: None,
+            }),
+            &[]
+        ).unwrap();
+
+        let res = app.execute_contract(
+            Addr::unchecked(SPENDER),
+            token.clone(),
+            &(ExecuteMsg::TransferFrom {
+                owner: HOLDER.to_string(),
+                recipient: staking.to_string(),
+                amount: Uint128::new(40),
+            }),
+            &[]
+        );
+
+        expect_error(res, "Receipts go back to the staking contract through Send");
+
+        // receipts the staking contract took back are burned from its balance
+        app.execute_contract(
+            staking.clone(),
+            token.clone(),
+            &(ExecuteMsg::Mint {
+                recipient: staking.to_string(),
+                amount: Uint128::new(40),
+            }),
+            &[]
+        ).unwrap();
+        app.execute_contract(staking.clone(), token.clone(), &burn_msg, &[]).unwrap();
+
+        let info: TokenInfoResponse = app
+            .wrap()
+            .query_wasm_smart(&token, &QueryMsg::TokenInfo {})
+            .unwrap();
+
+        assert_eq!(info.total_supply, Uint128::new(100));
+        assert_eq!(query_balance(&app, &token, staking.as_str()), Uint128::zero());
+    }
+
+    #[test]
+    fn transfers_report_to_staking_contract() {
+        let (mut app, staking, token) = proper_instantiate();
+
+        app.execute_contract(
+            staking.clone(),
+            token.clone(),
+            &(ExecuteMsg::Mint {
+                recipient: HOLDER.to_string(),
+                amount: Uint128::new(100),
+            }),
+            &[]
+        ).unwrap();
+
+        app.execute_contract(
+            Addr::unchecked(HOLDER),
+            token.clone(),
+            &(ExecuteMsg::Transfer {
+                recipient: SPENDER.to_string(),
+                amount: Uint128::new(30),
+            }),
+            &[]
+        ).unwrap();
+
+        let res = app.execute_contract(
+            Addr::unchecked(SPENDER),
+            token.clone(),
+            &(ExecuteMsg::TransferFrom {
+                owner: HOLDER.to_string(),
+                recipient: SPENDER.to_string(),
+                amount: Uint128::new(20),
+            }),
+            &[]
+        );
+
+        expect_error(res, "No allowance for this account");
+
+        app.execute_contract(
+            Addr::unchecked(HOLDER),
+            token.clone(),
+            &(ExecuteMsg::IncreaseAllowance {
+                spender: SPENDER.to_string(),
+                amount: Uint128::new(50),
+                expires: Some(Expiration::AtHeight(app.block_info().height + 10)),
+            }),
+            &[]
+        ).unwrap();
+
+        app.execute_contract(
+            Addr::unchecked(SPENDER),
+            token.clone(),
+            &(ExecuteMsg::TransferFrom {
+                owner: HOLDER.to_string(),
+                recipient: SPENDER.to_string(),
+                amount: Uint128::new(20),
+            }),
+            &[]
+        ).unwrap();
+
+        let allowance: AllowanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &token,
+                &(QueryMsg::Allowance {
+                    owner: HOLDER.to_string(),
+                    spender: SPENDER.to_string(),
+                })
+            )
+            .unwrap();
+
+        assert_eq!(allowance.allowance, Uint128::new(30));
+        assert_eq!(query_balance(&app, &token, HOLDER), Uint128::new(50));
+        assert_eq!(query_balance(&app, &token, SPENDER), Uint128::new(50));
+
+        // every balance move between holders reached the staking contract
+        let hooks: Vec<ReceiptHookMsg> = app
+            .wrap()
+            .query_wasm_smart(&staking, &Empty {})
+            .unwrap();
+
+        assert_eq!(hooks, vec![
+            ReceiptHookMsg::ReceiptTransfer {
+                from: HOLDER.to_string(),
+                to: SPENDER.to_string(),
+                amount: Uint128::new(30),
+            },
+            ReceiptHookMsg::ReceiptTransfer {
+                from: HOLDER.to_string(),
+                to: SPENDER.to_string(),
+                amount: Uint128::new(20),
+            }
+        ]);
+    }
+}
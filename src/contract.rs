@@ -1,36 +1,136 @@
-use std::str::FromStr;
+use std::collections::BTreeMap;
 
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
+    from_json,
     to_json_binary,
     Addr,
     BankMsg,
     Binary,
     Coin,
     CosmosMsg,
+    Decimal256,
     Deps,
     DepsMut,
+    Empty,
     Env,
     MessageInfo,
+    Order,
+    Reply,
     Response,
     StdError,
     StdResult,
+    Storage,
+    SubMsg,
     Timestamp,
     Uint128,
     Uint256,
+    WasmMsg,
 };
-use cw2::set_contract_version;
+use cw2::{ get_contract_version, set_contract_version };
+use cw20::{
+    BalanceResponse,
+    Cw20ExecuteMsg,
+    Cw20QueryMsg,
+    Cw20ReceiveMsg,
+    Denom,
+    UncheckedDenom,
+};
+use cw_storage_plus::Bound;
+use cw_utils::{ parse_reply_instantiate_data, Expiration };
+use injex_receipt_token::msg::{
+    ExecuteMsg as ReceiptTokenExecuteMsg,
+    InstantiateMsg as ReceiptTokenInstantiateMsg,
+    QueryMsg as ReceiptTokenQueryMsg,
+};
+use semver::Version;
 
 use crate::error::ContractError;
-use crate::msg::{ ExecuteMsg, InstantiateMsg, QueryMsg };
-use crate::state::{ Config, StakerInfo, State, ADMIN, CONFIG, PERCENTS, STATE, USER_STAKINGS };
+use crate::math::{
+    accrued_reward,
+    compound_index,
+    compounded_reward,
+    emission_index,
+    mul_div_floor,
+    simple_index,
+    ONE,
+    SECONDS_IN_YEAR,
+};
+use crate::migrations::run_migrations;
+use crate::msg::{
+    ExecuteMsg,
+    InfoResponse,
+    InstantiateMsg,
+    MigrateMsg,
+    ProjectedRewards,
+    QueryMsg,
+    ReceiveMsg,
+    RemainingCapacityResponse,
+    RewardAmount,
+    SimulationResponse,
+    SolvencyResponse,
+    StakeChangedHookMsg,
+    StakedBalanceAtHeightResponse,
+    StakerSummary,
+    TotalPowerAtHeightResponse,
+    TotalStakedAtHeightResponse,
+    VotingPowerAtHeightResponse,
+};
+use crate::state::{
+    AccrualMode,
+    AprChange,
+    Config,
+    LockTier,
+    PauseInfo,
+    PendingAdmin,
+    PendingWithdrawal,
+    RewardMode,
+    RewardStream,
+    Role,
+    StakerInfo,
+    StakerReward,
+    State,
+    ADMIN,
+    CONFIG,
+    DAO,
+    HOOKS,
+    LOCK_EXPIRIES,
+    PAUSE,
+    PENDING_ADMIN,
+    PENDING_WITHDRAWALS,
+    PERCENTS,
+    RECEIPT_SENDER,
+    REWARD_FUNDERS,
+    ROLES,
+    STAKED_BALANCES,
+    STAKED_TOTAL,
+    STATE,
+    USER_STAKINGS,
+};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "injex-staking";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
-const SECONDS_IN_YEAR: Uint256 = Uint256::from_u128(31_536_000_u128);
-pub const ONE: Uint256 = Uint256::from_u128(1000000000000000000_u128);
+
+// page size of `ListStakers`
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
+// reply to a stake change hook that failed during `EmergencyWithdraw`
+const HOOK_FAILURE_REPLY_ID: u64 = 1;
+
+// reply carrying the address of the instantiated receipt token
+const RECEIPT_TOKEN_REPLY_ID: u64 = 2;
+
+// highest lock tier multiplier, 10x
+const MAX_LOCK_MULTIPLIER: Uint256 = Uint256::from_u128(100_000_u128);
+
+// most timestamps `ProjectRewardsSeries` returns
+const MAX_PROJECTION_POINTS: u32 = 100;
+
+/// Every stream's index at each lock end an index update passed
+type ExpiredLocks = BTreeMap<u64, Vec<Uint256>>;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -39,31 +139,109 @@ pub fn instantiate(
     info: MessageInfo,
     msg: InstantiateMsg
 ) -> Result<Response, ContractError> {
+    validate_lock_tiers(&msg.lock_tiers)?;
+    validate_staking_caps(msg.min_stake_amount, msg.max_stake_per_user)?;
+
+    if let (Some(start), Some(end)) = (msg.rewards_start, msg.rewards_end) {
+        if start >= end {
+            return Err(ContractError::InvalidRewardsWindow {});
+        }
+    }
+
     let config = Config {
-        apr: msg.apr,
-        injex_token: msg.injex_token,
+        reward_mode: msg.reward_mode,
+        accrual_mode: msg.accrual_mode,
+        stake_denom: msg.stake_denom.into_checked(deps.as_ref())?,
+        unbonding_period: msg.unbonding_period,
+        lock_tiers: msg.lock_tiers,
+        rewards_start: msg.rewards_start,
+        rewards_end: msg.rewards_end,
+        stake_requires_start: msg.stake_requires_start,
+        max_total_staked: msg.max_total_staked,
+        min_stake_amount: msg.min_stake_amount,
+        max_stake_per_user: msg.max_stake_per_user,
+        receipt_token: None,
+    };
+
+    let dao = match msg.dao {
+        Some(dao) => deps.api.addr_validate(&dao)?,
+        None => info.sender.clone(),
     };
 
-    let ci_current = ONE;
+    let mut reward_streams: Vec<RewardStream> = vec![];
+
+    for stream in msg.reward_streams {
+        validate_stream_rate(&config, stream.apr, stream.emission_rate)?;
+
+        let denom = stream.denom.into_checked(deps.as_ref())?;
+
+        if reward_streams.iter().any(|existing| existing.denom == denom) {
+            return Err(ContractError::RewardStreamExists {});
+        }
+
+        reward_streams.push(RewardStream {
+            denom,
+            apr: stream.apr,
+            emission_rate: stream.emission_rate,
+            emission_end: None,
+            ci_current: ONE,
+            total_withdrawn: Uint256::zero(),
+            total_liability: Uint256::zero(),
+            reward_reserve: Uint256::zero(),
+            apr_schedule: vec![],
+            active: true,
+        });
+    }
 
     let state = State {
-        total_withdrawn: Uint256::zero(),
         total_staked: Uint256::zero(),
-        ci_current,
+        total_unbonding: Uint256::zero(),
+        total_weighted_staked: Uint256::zero(),
+        staker_count: 0,
         ci_time_current: _env.block.time,
+        reward_streams,
     };
 
     let admin = msg.admin;
+
+    // the token address is stored once its instantiation replies
+    let receipt_token = msg.receipt_token
+        .map(|token| -> StdResult<SubMsg> {
+            // only this contract may migrate the token, the owner could otherwise swap its code
+            // for one that stops reporting transfers
+            let instantiate_msg = WasmMsg::Instantiate {
+                admin: Some(_env.contract.address.to_string()),
+                code_id: token.code_id,
+                msg: to_json_binary(
+                    &(ReceiptTokenInstantiateMsg {
+                        name: token.name,
+                        symbol: token.symbol.clone(),
+                        decimals: token.decimals,
+                    })
+                )?,
+                funds: vec![],
+                label: format!("{} receipt token", token.symbol),
+            };
+
+            Ok(SubMsg::reply_on_success(instantiate_msg, RECEIPT_TOKEN_REPLY_ID))
+        })
+        .transpose()?;
+
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     CONFIG.save(deps.storage, &config)?;
     ADMIN.save(deps.storage, &deps.api.addr_validate(&admin)?)?;
+    DAO.save(deps.storage, &dao)?;
     STATE.save(deps.storage, &state)?;
+    STAKED_TOTAL.save(deps.storage, &state.total_staked, _env.block.height)?;
+    PAUSE.save(deps.storage, &PauseInfo::default())?;
 
     Ok(
         Response::new()
+            .add_submessages(receipt_token)
             .add_attribute("method", "instantiate")
             .add_attribute("owner", info.sender)
-            .add_attribute("apr", msg.apr.to_string())
+            .add_attribute("reward_streams", state.reward_streams.len().to_string())
+            .add_attribute("unbonding_period", msg.unbonding_period.to_string())
     )
 }
 
@@ -75,13 +253,130 @@ pub fn execute(
     msg: ExecuteMsg
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::Stake {} => stake(deps, _env, info),
+        ExecuteMsg::Stake { lock_duration } => stake(deps, _env, info, lock_duration),
+        ExecuteMsg::Receive(msg) => receive(deps, _env, info, msg),
+        ExecuteMsg::FundRewards {} => fund_rewards(deps, info),
+        ExecuteMsg::WithdrawExcessRewards { denom, amount } =>
+            withdraw_excess_rewards(deps, _env, info, denom, amount),
         ExecuteMsg::Claim {} => claim_rewards(deps, _env, info),
+        ExecuteMsg::Compound {} => compound(deps, _env, info),
+        ExecuteMsg::SetAutoCompound { enabled } => set_auto_compound(deps, info, enabled),
         ExecuteMsg::Unstake { amount } => unstake(deps, _env, info, amount),
-        ExecuteMsg::ChangeApr { new_apr } => change_apr(deps, _env, info, new_apr),
-        ExecuteMsg::ChangeAdmin { address } => change_admin(deps, info, address),
-        ExecuteMsg::ChangeInjexToken { new_injex_token } =>
-            change_injex_token(deps, info, new_injex_token),
+        ExecuteMsg::WithdrawUnbonded {} => withdraw_unbonded(deps, _env, info),
+        ExecuteMsg::EmergencyWithdraw {} => emergency_withdraw(deps, _env, info),
+        ExecuteMsg::ReceiptTransfer { from, to, amount } =>
+            receipt_transfer(deps, _env, info, from, to, amount),
+        ExecuteMsg::AddRewardStream { denom, apr, emission_rate } =>
+            add_reward_stream(deps, _env, info, denom, apr, emission_rate),
+        ExecuteMsg::RemoveRewardStream { denom } => remove_reward_stream(deps, _env, info, denom),
+        ExecuteMsg::ChangeApr { denom, new_apr } => change_apr(deps, _env, info, denom, new_apr),
+        ExecuteMsg::ChangeEmissionRate { denom, emission_rate } =>
+            change_emission_rate(deps, _env, info, denom, emission_rate),
+        ExecuteMsg::SetEmissionBudget { denom, amount, duration } =>
+            set_emission_budget(deps, _env, info, denom, amount, duration),
+        ExecuteMsg::ScheduleAprChange { denom, effective_time, apr } =>
+            schedule_apr_change(deps, _env, info, denom, effective_time, apr),
+        ExecuteMsg::CancelAprChange { denom, effective_time } =>
+            cancel_apr_change(deps, _env, info, denom, effective_time),
+        ExecuteMsg::ProposeAdmin { address, expiry } =>
+            propose_admin(deps, _env, info, address, expiry),
+        ExecuteMsg::AcceptAdmin {} => accept_admin(deps, _env, info),
+        ExecuteMsg::CancelAdminProposal {} => cancel_admin_proposal(deps, info),
+        ExecuteMsg::RenounceAdmin {} => renounce_admin(deps, info),
+        ExecuteMsg::ExtendRewardsEnd { rewards_end } =>
+            extend_rewards_end(deps, _env, info, rewards_end),
+        ExecuteMsg::ChangeStakeDenom { new_stake_denom } =>
+            change_stake_denom(deps, info, new_stake_denom),
+        ExecuteMsg::ChangeUnbondingPeriod { new_unbonding_period } =>
+            change_unbonding_period(deps, info, new_unbonding_period),
+        ExecuteMsg::UpdateLockTiers { lock_tiers } => update_lock_tiers(deps, info, lock_tiers),
+        ExecuteMsg::UpdateStakingCaps { max_total_staked, min_stake_amount, max_stake_per_user } =>
+            update_staking_caps(deps, info, max_total_staked, min_stake_amount, max_stake_per_user),
+        ExecuteMsg::GrantRole { role, address } => grant_role(deps, info, role, address),
+        ExecuteMsg::RevokeRole { role, address } => revoke_role(deps, info, role, address),
+        ExecuteMsg::AddHook { address } => add_hook(deps, info, address),
+        ExecuteMsg::RemoveHook { address } => remove_hook(deps, info, address),
+        ExecuteMsg::Pause { reason } => pause(deps, info, reason),
+        ExecuteMsg::Unpause {} => unpause(deps, info),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(mut deps: DepsMut, env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+
+    if stored.contract != CONTRACT_NAME {
+        return Err(ContractError::InvalidMigrationContract { contract: stored.contract });
+    }
+
+    let stored_version = parse_version(&stored.version)?;
+    let current_version = parse_version(CONTRACT_VERSION)?;
+
+    if stored_version > current_version {
+        return Err(ContractError::CannotDowngrade {
+            stored: stored.version,
+            current: CONTRACT_VERSION.to_string(),
+        });
+    }
+
+    let applied = run_migrations(deps.branch(), &env, &stored_version, &current_version)?;
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(
+        Response::new()
+            .add_attribute("method", "migrate")
+            .add_attribute("from_version", stored.version)
+            .add_attribute("to_version", CONTRACT_VERSION)
+            .add_attribute("applied_migrations", applied.join(","))
+    )
+}
+
+fn parse_version(version: &str) -> Result<Version, ContractError> {
+    Version::parse(version).map_err(|_| ContractError::InvalidVersion {
+        version: version.to_string(),
+    })
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        RECEIPT_TOKEN_REPLY_ID => {
+            let res = parse_reply_instantiate_data(msg)?;
+            let receipt_token = deps.api.addr_validate(&res.contract_address)?;
+
+            // a plain CW20 would let positions drift from the tokens on the first transfer
+            let staking_contract: Addr = deps.querier
+                .query_wasm_smart(&receipt_token, &ReceiptTokenQueryMsg::StakingContract {})
+                .map_err(|_| ContractError::InvalidReceiptToken {})?;
+
+            if staking_contract != env.contract.address {
+                return Err(ContractError::InvalidReceiptToken {});
+            }
+
+            CONFIG.update(deps.storage, |mut config| -> StdResult<Config> {
+                config.receipt_token = Some(receipt_token.clone());
+
+                Ok(config)
+            })?;
+
+            Ok(
+                Response::new()
+                    .add_attribute("receipt_token", receipt_token)
+                    .add_attribute("method", "reply_receipt_token")
+            )
+        }
+        // the failed hook was rolled back on its own, the emergency withdrawal goes through
+        HOOK_FAILURE_REPLY_ID => {
+            let error = msg.result.into_result().err().unwrap_or_default();
+
+            Ok(
+                Response::new()
+                    .add_attribute("hook_error", error)
+                    .add_attribute("method", "reply_hook_failure")
+            )
+        }
+        id => Err(ContractError::UnknownReplyId { id }),
     }
 }
 
@@ -89,73 +384,188 @@ pub fn execute(
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::GetState {} => to_json_binary(&query_state(deps)?),
-        QueryMsg::GetApr {} => to_json_binary(&query_apr(deps)?),
-        QueryMsg::GetInjexToken {} => to_json_binary(&query_injex_token(deps)?),
+        QueryMsg::GetRewardStreams {} => to_json_binary(&query_reward_streams(deps)?),
+        QueryMsg::GetUnbondingPeriod {} => to_json_binary(&query_unbonding_period(deps)?),
+        QueryMsg::GetLockTiers {} => to_json_binary(&query_lock_tiers(deps)?),
+        QueryMsg::GetConfig {} => to_json_binary(&query_config(deps)?),
+        QueryMsg::GetRewardMode {} => to_json_binary(&query_reward_mode(deps)?),
+        QueryMsg::GetRemainingCapacity { user } =>
+            to_json_binary(&query_remaining_capacity(deps, user)?),
+        QueryMsg::GetAprSchedule { denom } =>
+            to_json_binary(&query_apr_schedule(deps, _env, denom)?),
+        QueryMsg::GetStakeDenom {} => to_json_binary(&query_stake_denom(deps)?),
         QueryMsg::GetTotalStaked {} => to_json_binary(&query_total_staked(deps)?),
         QueryMsg::GetTotalWithdrawn {} => to_json_binary(&query_total_withdrawn(deps)?),
         QueryMsg::GetStakerInfo { user } => to_json_binary(&query_staker_indo(deps, user)?),
         QueryMsg::GetClaimableAmount { user } =>
             to_json_binary(&query_claimable_tokens(deps, _env, user)?),
+        QueryMsg::ListStakers { start_after, limit } =>
+            to_json_binary(&query_list_stakers(deps, _env, start_after, limit)?),
+        QueryMsg::GetStakerCount {} => to_json_binary(&query_staker_count(deps)?),
+        QueryMsg::StakedBalanceAtHeight { user, height } =>
+            to_json_binary(&query_staked_balance_at_height(deps, _env, user, height)?),
+        QueryMsg::TotalStakedAtHeight { height } =>
+            to_json_binary(&query_total_staked_at_height(deps, _env, height)?),
+        QueryMsg::VotingPowerAtHeight { address, height } =>
+            to_json_binary(&query_voting_power_at_height(deps, _env, address, height)?),
+        QueryMsg::TotalPowerAtHeight { height } =>
+            to_json_binary(&query_total_power_at_height(deps, _env, height)?),
+        QueryMsg::Dao {} => to_json_binary(&query_dao(deps)?),
+        QueryMsg::Info {} => to_json_binary(&query_info(deps)?),
+        QueryMsg::SimulateStake { user, amount, lock_duration } => {
+            let simulation = query_simulate_stake(deps, _env, user, amount, lock_duration);
+
+            to_json_binary(&simulation.map_err(to_std_error)?)
+        }
+        QueryMsg::SimulateUnstake { user, amount } => {
+            let simulation = query_simulate_unstake(deps, _env, user, amount);
+
+            to_json_binary(&simulation.map_err(to_std_error)?)
+        }
+        QueryMsg::ProjectRewards { user, at_time } =>
+            to_json_binary(&query_project_rewards(deps, _env, user, at_time)?),
+        QueryMsg::ProjectRewardsSeries { user, end_time, points } =>
+            to_json_binary(&query_project_rewards_series(deps, _env, user, end_time, points)?),
+        QueryMsg::GetPendingWithdrawals { user } =>
+            to_json_binary(&query_pending_withdrawals(deps, user)?),
+        QueryMsg::GetPauseInfo {} => to_json_binary(&query_pause_info(deps)?),
+        QueryMsg::GetAdmin {} => to_json_binary(&query_admin(deps)?),
+        QueryMsg::GetPendingAdmin {} => to_json_binary(&query_pending_admin(deps)?),
+        QueryMsg::GetSolvency {} => to_json_binary(&query_solvency(deps, _env)?),
+        QueryMsg::GetFundedRewards { user } => to_json_binary(&query_funded_rewards(deps, user)?),
+        QueryMsg::GetRoleHolders { role } => to_json_binary(&query_role_holders(deps, role)?),
+        QueryMsg::ListHooks {} => to_json_binary(&query_hooks(deps)?),
     }
 }
 
-pub fn stake(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+pub fn stake(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    lock_duration: Option<u64>
+) -> Result<Response, ContractError> {
     if info.funds.len() != 1 {
         return Err(ContractError::InvalidFunds {});
     }
 
-    let config = CONFIG.load(deps.storage).unwrap();
+    let config = CONFIG.load(deps.storage)?;
 
     let coin = &info.funds[0];
 
-    if coin.denom != config.injex_token {
-        return Err(ContractError::InvalidCoin {});
+    if config.stake_denom != Denom::Native(coin.denom.clone()) {
+        return Err(ContractError::InvalidCoin { denom: coin.denom.clone() });
     }
 
     let amount = Uint256::from_uint128(coin.amount);
-    let (new_ci, curr_block_time) = get_new_ci(&deps, env).unwrap();
 
-    USER_STAKINGS.update(
-        deps.storage,
-        info.sender.clone(),
-        |staking| -> Result<StakerInfo, StdError> {
-            let mut staking = staking.unwrap_or_else(|| StakerInfo {
-                block_time: curr_block_time,
-                ci_0: Uint256::zero(),
-                reward: Uint256::zero(),
-                staked: Uint256::zero(),
-            });
+    execute_stake(deps, env, info.sender, amount, lock_duration)
+}
+
+pub fn receive(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    wrapper: Cw20ReceiveMsg
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
 
-            if staking.staked != Uint256::zero() {
-                let reward = calculate_reward(staking.staked, new_ci, staking.ci_0).unwrap();
+    if wrapper.amount.is_zero() {
+        return Err(ContractError::InvalidFunds {});
+    }
 
-                staking.reward += reward;
-                staking.staked += amount;
-            } else {
-                staking.staked = amount;
-            }
+    let sender = deps.api.addr_validate(&wrapper.sender)?;
+    let amount = Uint256::from_uint128(wrapper.amount);
+
+    // receipt tokens come back only to be burned against the position they were taken from,
+    // `wrapper.sender` is the spender on a `SendFrom`
+    if config.receipt_token.as_ref() == Some(&info.sender) {
+        let owner = RECEIPT_SENDER.load(deps.storage)?;
+        RECEIPT_SENDER.remove(deps.storage);
+
+        return match from_json(&wrapper.msg)? {
+            ReceiveMsg::Unstake {} => execute_unstake(deps, env, owner, amount),
+            ReceiveMsg::EmergencyWithdraw {} =>
+                execute_emergency_withdraw(deps, env, owner, amount),
+            _ => Err(ContractError::InvalidCoin { denom: info.sender.to_string() }),
+        };
+    }
 
-            staking.ci_0 = new_ci;
+    match from_json(&wrapper.msg)? {
+        ReceiveMsg::Stake { lock_duration } => {
+            // only the configured CW20 contract can deliver stake through the hook
+            if config.stake_denom != Denom::Cw20(info.sender.clone()) {
+                return Err(ContractError::InvalidCoin { denom: info.sender.to_string() });
+            }
 
-            Ok(staking)
+            execute_stake(deps, env, sender, amount, lock_duration)
         }
-    ).unwrap();
+        ReceiveMsg::FundRewards {} =>
+            execute_fund_rewards(deps, sender, Denom::Cw20(info.sender), amount),
+        ReceiveMsg::Unstake {} | ReceiveMsg::EmergencyWithdraw {} =>
+            Err(ContractError::InvalidCoin { denom: info.sender.to_string() }),
+    }
+}
 
-    STATE.update(
-        deps.storage,
-        |mut state| -> Result<State, StdError> {
-            state.ci_current = new_ci;
-            state.ci_time_current = curr_block_time;
-            state.total_staked += amount;
+fn execute_stake(
+    deps: DepsMut,
+    env: Env,
+    staker: Addr,
+    amount: Uint256,
+    lock_duration: Option<u64>
+) -> Result<Response, ContractError> {
+    ensure_not_paused(deps.as_ref())?;
 
-            Ok(state)
-        }
-    ).unwrap();
+    let config = CONFIG.load(deps.storage)?;
+
+    ensure_stake_started(&config, env.block.time)?;
+
+    let mut state = STATE.load(deps.storage)?;
+
+    let expired = update_ci(deps.storage, &config, &mut state, env.block.time)?;
+
+    let mut staking = USER_STAKINGS.may_load(deps.storage, staker.clone())?.unwrap_or_else(|| {
+        new_staker_info(env.block.time)
+    });
+
+    let old_staked = staking.staked;
+    let old_boost = lock_boost(&staking)?;
+
+    update_rewards(deps.storage, &config, &mut staking, &state, &expired)?;
+
+    // taken after settling, the boost of an expired lock already left the total weight
+    let old_weight = get_weight(&staking)?;
+
+    let compounded = auto_compound(deps.as_ref(), &config, &mut state, &mut staking)?;
+
+    apply_lock(&config, &mut staking, amount, lock_duration, env.block.time)?;
+
+    state.total_staked = state.total_staked.checked_add(amount)?;
+    update_total_weight(&mut state, old_weight, get_weight(&staking)?)?;
+    update_staker_count(&mut state, old_staked, staking.staked);
+
+    validate_stake_caps(&config, &state, &staking, compounded)?;
+
+    update_lock_expiry(deps.storage, old_boost, lock_boost(&staking)?)?;
+    USER_STAKINGS.save(deps.storage, staker.clone(), &staking)?;
+    STATE.save(deps.storage, &state)?;
+    snapshot_stake(deps.storage, &staker, staking.staked, &state, env.block.height)?;
+
+    let receipt_msgs = receipt_token_msgs(
+        &config,
+        &staker,
+        amount.checked_add(compounded)?,
+        Uint256::zero()
+    )?;
+    let hooks = stake_changed_hooks(deps.storage, &staker, old_staked, staking.staked)?;
 
     Ok(
         Response::new()
-            .add_attribute("user", info.sender.clone())
+            .add_messages(receipt_msgs)
+            .add_submessages(hooks)
+            .add_attribute("user", staker)
             .add_attribute("amount_staked", amount)
+            .add_attribute("amount_compounded", compounded)
+            .add_attribute("lock_end", staking.lock_end.seconds().to_string())
             .add_attribute("method", "execute_stake")
     )
 }
@@ -166,319 +576,2577 @@ pub fn unstake(
     info: MessageInfo,
     amount: Uint256
 ) -> Result<Response, ContractError> {
-    let config = CONFIG.load(deps.storage).unwrap();
-
-    let staking_info = USER_STAKINGS.load(deps.storage, info.sender.clone()).unwrap_or_else(
-        |_| StakerInfo {
-            block_time: Timestamp::from_seconds(0),
-            ci_0: Uint256::zero(),
-            reward: Uint256::zero(),
-            staked: Uint256::zero(),
-        }
-    );
+    let config = CONFIG.load(deps.storage)?;
 
-    if staking_info.staked == Uint256::zero() {
-        return Err(ContractError::CannotUnstake {});
-    } else if staking_info.staked < amount {
-        return Err(ContractError::CannotUnstakeAmount {});
+    // a tokenized position only shrinks against the receipt tokens it was minted
+    if config.receipt_token.is_some() {
+        return Err(ContractError::ReceiptTokenRequired {});
     }
 
-    let (new_ci, curr_block_time) = get_new_ci(&deps, env).unwrap();
+    execute_unstake(deps, env, info.sender, amount)
+}
 
-    USER_STAKINGS.update(
-        deps.storage,
-        info.sender.clone(),
-        |staking| -> Result<StakerInfo, StdError> {
-            let mut staking = staking.unwrap();
+fn execute_unstake(
+    deps: DepsMut,
+    env: Env,
+    staker: Addr,
+    amount: Uint256
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
 
-            let reward = calculate_reward(staking.staked, new_ci, staking.ci_0).unwrap();
+    let mut staking = USER_STAKINGS.may_load(deps.storage, staker.clone())?.unwrap_or_else(|| {
+        new_staker_info(Timestamp::from_seconds(0))
+    });
 
-            staking.reward += reward;
-            staking.staked -= amount;
+    ensure_can_unstake(&staking, amount, env.block.time)?;
 
-            staking.ci_0 = new_ci;
+    let mut state = STATE.load(deps.storage)?;
 
-            Ok(staking)
-        }
-    ).unwrap();
+    let expired = update_ci(deps.storage, &config, &mut state, env.block.time)?;
 
-    STATE.update(
+    let old_staked = staking.staked;
+    let old_boost = lock_boost(&staking)?;
+
+    update_rewards(deps.storage, &config, &mut staking, &state, &expired)?;
+
+    let old_weight = get_weight(&staking)?;
+
+    let compounded = auto_compound(deps.as_ref(), &config, &mut state, &mut staking)?;
+
+    staking.staked = staking.staked.checked_sub(amount)?;
+    state.total_staked = state.total_staked.checked_sub(amount)?;
+    state.total_unbonding = state.total_unbonding.checked_add(amount)?;
+    update_total_weight(&mut state, old_weight, get_weight(&staking)?)?;
+    update_staker_count(&mut state, old_staked, staking.staked);
+
+    validate_remaining_stake(&config, &staking)?;
+
+    update_lock_expiry(deps.storage, old_boost, lock_boost(&staking)?)?;
+    USER_STAKINGS.save(deps.storage, staker.clone(), &staking)?;
+    STATE.save(deps.storage, &state)?;
+    snapshot_stake(deps.storage, &staker, staking.staked, &state, env.block.height)?;
+
+    let release_time = env.block.time.plus_seconds(config.unbonding_period);
+
+    PENDING_WITHDRAWALS.update(
         deps.storage,
-        |mut state| -> Result<State, StdError> {
-            state.ci_current = new_ci;
-            state.ci_time_current = curr_block_time;
-            state.total_staked -= amount;
+        staker.clone(),
+        |withdrawals| -> Result<Vec<PendingWithdrawal>, StdError> {
+            let mut withdrawals = withdrawals.unwrap_or_default();
 
-            Ok(state)
+            withdrawals.push(PendingWithdrawal {
+                amount,
+                release_time,
+            });
+
+            Ok(withdrawals)
         }
-    ).unwrap();
+    )?;
 
-    let swap_msg = BankMsg::Send {
-        to_address: info.sender.clone().to_string(),
-        amount: vec![Coin {
-            amount: Uint128::from_str(&amount.to_string())?,
-            denom: config.injex_token.to_string(),
-        }],
-    };
+    let receipt_msgs = receipt_token_msgs(&config, &staker, compounded, amount)?;
+    let hooks = stake_changed_hooks(deps.storage, &staker, old_staked, staking.staked)?;
 
     Ok(
         Response::new()
-            .add_message(CosmosMsg::Bank(swap_msg))
-            .add_attribute("user", info.sender.clone())
+            .add_messages(receipt_msgs)
+            .add_submessages(hooks)
+            .add_attribute("user", staker.clone())
             .add_attribute("amount_unstaked", amount)
+            .add_attribute("amount_compounded", compounded)
+            .add_attribute("release_time", release_time.seconds().to_string())
             .add_attribute("method", "execute_unstake")
     )
 }
 
-pub fn claim_rewards(
+/// Moves stake along with a receipt token transfer, each side keeps the rewards it accrued so far
+pub fn receipt_transfer(
     deps: DepsMut,
     env: Env,
-    info: MessageInfo
+    info: MessageInfo,
+    from: String,
+    to: String,
+    amount: Uint128
 ) -> Result<Response, ContractError> {
-    let config = CONFIG.load(deps.storage).unwrap();
-    let state = STATE.load(deps.storage).unwrap();
+    let config = CONFIG.load(deps.storage)?;
 
-    let staking_info = USER_STAKINGS.load(deps.storage, info.sender.clone()).unwrap_or_else(
-        |_| StakerInfo {
-            block_time: Timestamp::from_seconds(0),
-            ci_0: Uint256::zero(),
-            reward: Uint256::zero(),
-            staked: Uint256::zero(),
-        }
-    );
+    if config.receipt_token.as_ref() != Some(&info.sender) {
+        return Err(ContractError::Unauthorized { sender: info.sender.to_string() });
+    }
 
-    let (new_ci, curr_block_time) = get_new_ci(&deps, env.clone()).unwrap();
+    let from = deps.api.addr_validate(&from)?;
+    let to = deps.api.addr_validate(&to)?;
+    let amount = Uint256::from_uint128(amount);
 
-    let reward =
-        calculate_reward(staking_info.staked, new_ci, staking_info.ci_0).unwrap() +
-        staking_info.reward;
+    // tokens sent back to the contract are settled by the `Receive` that follows
+    if to == env.contract.address {
+        RECEIPT_SENDER.save(deps.storage, &from)?;
 
-    if reward == Uint256::zero() {
-        return Err(ContractError::CannotClaim {});
+        return Ok(Response::new().add_attribute("method", "execute_receipt_transfer"));
     }
 
-    let balance_res = deps.querier.query_balance(
-        env.clone().contract.address.to_string(),
-        config.injex_token.clone()
-    )?;
+    if from == to {
+        return Ok(Response::new().add_attribute("method", "execute_receipt_transfer"));
+    }
 
-    let balance = balance_res.amount;
+    ensure_not_paused(deps.as_ref())?;
 
-    if Uint256::from_uint128(balance) < reward + state.total_staked {
-        return Err(ContractError::InsufficientContractBalance {});
-    }
+    let mut sender = USER_STAKINGS.may_load(deps.storage, from.clone())?.unwrap_or_else(|| {
+        new_staker_info(Timestamp::from_seconds(0))
+    });
 
-    USER_STAKINGS.update(
-        deps.storage,
-        info.sender.clone(),
-        |staking| -> Result<StakerInfo, StdError> {
-            let mut staking = staking.unwrap();
+    // locked tokens stay with their position
+    ensure_can_unstake(&sender, amount, env.block.time)?;
 
-            staking.reward = Uint256::zero();
+    let mut recipient = USER_STAKINGS.may_load(deps.storage, to.clone())?.unwrap_or_else(|| {
+        new_staker_info(env.block.time)
+    });
 
-            staking.ci_0 = new_ci;
+    let mut state = STATE.load(deps.storage)?;
 
-            Ok(staking)
-        }
-    ).unwrap();
+    let expired = update_ci(deps.storage, &config, &mut state, env.block.time)?;
 
-    STATE.update(
-        deps.storage,
-        |mut state| -> Result<State, StdError> {
-            state.ci_current = new_ci;
-            state.ci_time_current = curr_block_time;
-            state.total_withdrawn += reward;
+    let old_sender_staked = sender.staked;
+    let old_recipient_staked = recipient.staked;
+    let old_sender_boost = lock_boost(&sender)?;
+    let old_recipient_boost = lock_boost(&recipient)?;
 
-            Ok(state)
-        }
-    ).unwrap();
+    update_rewards(deps.storage, &config, &mut sender, &state, &expired)?;
+    update_rewards(deps.storage, &config, &mut recipient, &state, &expired)?;
 
-    let swap_msg = BankMsg::Send {
-        to_address: info.sender.clone().to_string(),
-        amount: vec![Coin {
-            amount: Uint128::from_str(&reward.to_string())?,
-            denom: config.injex_token.to_string(),
-        }],
-    };
+    let old_weight = get_weight(&sender)?.checked_add(get_weight(&recipient)?)?;
+
+    sender.staked = sender.staked.checked_sub(amount)?;
+    add_stake(&mut recipient, amount)?;
+
+    let new_weight = get_weight(&sender)?.checked_add(get_weight(&recipient)?)?;
+
+    update_total_weight(&mut state, old_weight, new_weight)?;
+    update_staker_count(&mut state, old_sender_staked, sender.staked);
+    update_staker_count(&mut state, old_recipient_staked, recipient.staked);
+
+    // the pool does not grow, a lowered `max_total_staked` must not freeze transfers
+    validate_remaining_stake(&config, &sender)?;
+    validate_position_caps(&config, &recipient, Uint256::zero())?;
+
+    update_lock_expiry(deps.storage, old_sender_boost, lock_boost(&sender)?)?;
+    update_lock_expiry(deps.storage, old_recipient_boost, lock_boost(&recipient)?)?;
+    USER_STAKINGS.save(deps.storage, from.clone(), &sender)?;
+    USER_STAKINGS.save(deps.storage, to.clone(), &recipient)?;
+    STATE.save(deps.storage, &state)?;
+    snapshot_stake(deps.storage, &from, sender.staked, &state, env.block.height)?;
+    snapshot_stake(deps.storage, &to, recipient.staked, &state, env.block.height)?;
+
+    let mut hooks = stake_changed_hooks(deps.storage, &from, old_sender_staked, sender.staked)?;
+    hooks.extend(
+        stake_changed_hooks(deps.storage, &to, old_recipient_staked, recipient.staked)?
+    );
 
     Ok(
         Response::new()
-            .add_message(CosmosMsg::Bank(swap_msg))
-            .add_attribute("user", info.sender.clone())
-            .add_attribute("amount_claimed", reward)
-            .add_attribute("method", "execute_claim")
+            .add_submessages(hooks)
+            .add_attribute("from", from)
+            .add_attribute("to", to)
+            .add_attribute("amount", amount)
+            .add_attribute("method", "execute_receipt_transfer")
     )
 }
 
-pub fn change_apr(
+pub fn withdraw_unbonded(
     deps: DepsMut,
     env: Env,
-    info: MessageInfo,
-    new_apr: Uint256
+    info: MessageInfo
 ) -> Result<Response, ContractError> {
-    let admin = ADMIN.load(deps.storage).unwrap();
+    let config = CONFIG.load(deps.storage)?;
 
-    if admin != info.sender.clone() {
-        return Err(ContractError::OnlyAdmin {});
-    }
+    let withdrawals = PENDING_WITHDRAWALS.may_load(
+        deps.storage,
+        info.sender.clone()
+    )?.unwrap_or_default();
 
-    if new_apr == Uint256::zero() {
-        return Err(ContractError::InvalidApr {});
+    let (released, pending): (Vec<PendingWithdrawal>, Vec<PendingWithdrawal>) = withdrawals
+        .into_iter()
+        .partition(|withdrawal| withdrawal.release_time <= env.block.time);
+
+    let amount = released
+        .iter()
+        .try_fold(Uint256::zero(), |total, withdrawal| total.checked_add(withdrawal.amount))?;
+
+    if amount == Uint256::zero() {
+        return Err(ContractError::NothingToWithdraw {});
     }
 
-    let (new_ci, curr_block_time) = get_new_ci(&deps, env).unwrap();
+    if pending.is_empty() {
+        PENDING_WITHDRAWALS.remove(deps.storage, info.sender.clone());
+    } else {
+        PENDING_WITHDRAWALS.save(deps.storage, info.sender.clone(), &pending)?;
+    }
 
     STATE.update(
         deps.storage,
         |mut state| -> Result<State, StdError> {
-            state.ci_current = new_ci;
-            state.ci_time_current = curr_block_time;
+            state.total_unbonding = state.total_unbonding.checked_sub(amount)?;
+
             Ok(state)
         }
-    ).unwrap();
-    CONFIG.update(
-        deps.storage,
-        |mut config| -> Result<Config, StdError> {
-            config.apr = new_apr;
+    )?;
 
-            Ok(config)
-        }
-    ).unwrap();
+    let swap_msg = send_tokens_msg(&config.stake_denom, &info.sender, amount)?;
+
+    Ok(
+        Response::new()
+            .add_message(swap_msg)
+            .add_attribute("user", info.sender.clone())
+            .add_attribute("amount_withdrawn", amount)
+            .add_attribute("method", "execute_withdraw_unbonded")
+    )
+}
 
-    Ok(Response::new().add_attribute("new_apr", new_apr).add_attribute("method", "execute_new_apr"))
+pub fn emergency_withdraw(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo
+) -> Result<Response, ContractError> {
+    execute_emergency_withdraw(deps, env, info.sender, Uint256::zero())
 }
 
-pub fn change_injex_token(
+/// `receipts` is the amount of receipt tokens sent to be burned against the position
+fn execute_emergency_withdraw(
     deps: DepsMut,
-    info: MessageInfo,
-    new_injex_token: String
+    env: Env,
+    staker: Addr,
+    receipts: Uint256
 ) -> Result<Response, ContractError> {
-    let admin = ADMIN.load(deps.storage).unwrap();
+    let config = CONFIG.load(deps.storage)?;
+    let mut state = STATE.load(deps.storage)?;
+    let paused = PAUSE.load(deps.storage)?.paused;
+
+    let mut staking = USER_STAKINGS.may_load(deps.storage, staker.clone())?.unwrap_or_else(
+        || new_staker_info(env.block.time)
+    );
 
-    if admin != info.sender.clone() {
-        return Err(ContractError::OnlyAdmin {});
+    // outside a pause a lock still holds, or a locked position could claim its boost and leave
+    if !paused && staking.lock_end > env.block.time {
+        return Err(ContractError::Locked { until: staking.lock_end.seconds() });
     }
 
-    CONFIG.update(
-        deps.storage,
-        |mut config| -> Result<Config, StdError> {
-            config.injex_token = new_injex_token;
+    // unbonding tokens had their receipts burned already, only the stake needs them
+    if config.receipt_token.is_some() && receipts != staking.staked {
+        return Err(ContractError::ReceiptAmountMismatch {
+            amount: receipts,
+            staked: staking.staked,
+        });
+    }
 
-            Ok(config)
+    // settle everyone else's rewards before the weight leaves the pool, the staker's own rewards
+    // are forfeited so an accrual that no longer computes must not keep the principal in
+    let mut settled_state = state.clone();
+    let mut settled_staking = staking.clone();
+
+    let old_boost = lock_boost(&staking)?;
+
+    let settled = project_ci(deps.storage, &config, &mut settled_state, env.block.time).and_then(
+        |expired| {
+            update_rewards(
+                deps.storage,
+                &config,
+                &mut settled_staking,
+                &settled_state,
+                &expired
+            )?;
+
+            Ok(expired)
         }
-    ).unwrap();
+    );
 
-    Ok(Response::new().add_attribute("method", "execute_new_injex_token"))
-}
+    if let Ok(expired) = settled {
+        save_expired_locks(deps.storage, &expired)?;
+        state = settled_state;
+        staking = settled_staking;
+    }
 
-pub fn change_admin(
-    deps: DepsMut,
-    info: MessageInfo,
-    new_admin: String
-) -> Result<Response, ContractError> {
-    let admin = ADMIN.load(deps.storage).unwrap();
+    // forfeited rewards are no longer owed
+    for (stream, staker_reward) in state.reward_streams.iter_mut().zip(staking.rewards.iter()) {
+        stream.total_liability = stream.total_liability.saturating_sub(staker_reward.reward);
+    }
 
-    if admin != info.sender.clone() {
-        return Err(ContractError::OnlyAdmin {});
+    let withdrawals = PENDING_WITHDRAWALS.may_load(
+        deps.storage,
+        staker.clone()
+    )?.unwrap_or_default();
+
+    let unbonding = withdrawals
+        .iter()
+        .try_fold(Uint256::zero(), |total, withdrawal| total.checked_add(withdrawal.amount))?;
+
+    // while paused unbonding tokens are released too, otherwise the principal still unbonds
+    if staking.staked == Uint256::zero() && (!paused || unbonding == Uint256::zero()) {
+        return Err(ContractError::NothingToWithdraw {});
     }
 
-    ADMIN.save(deps.storage, &deps.api.addr_validate(&new_admin)?)?;
+    state.total_staked = state.total_staked.checked_sub(staking.staked)?;
+    update_total_weight(&mut state, get_weight(&staking)?, Uint256::zero())?;
+    update_staker_count(&mut state, staking.staked, Uint256::zero());
 
-    Ok(Response::new().add_attribute("method", "execute_new_admin"))
-}
+    update_lock_expiry(deps.storage, old_boost, None)?;
+    USER_STAKINGS.remove(deps.storage, staker.clone());
 
-fn get_new_ci(deps: &DepsMut, env: Env) -> StdResult<(Uint256, Timestamp)> {
-    let config = CONFIG.load(deps.storage).unwrap();
-    let state = STATE.load(deps.storage).unwrap();
+    let mut response = Response::new();
 
-    let curr_ci = state.ci_current;
-    let apr = config.apr;
-    let curr_block_time = env.block.time;
-    let new_ci = if state.total_staked == Uint256::zero() {
-        state.ci_current
-    } else {
-        let old_block_time = state.ci_time_current;
+    if paused {
+        let amount = staking.staked.checked_add(unbonding)?;
 
-        calculate_ci(
-            curr_ci,
-            apr,
-            Uint256::from_u128((curr_block_time.seconds() - old_block_time.seconds()).into())
-        ).unwrap()
-    };
+        state.total_unbonding = state.total_unbonding.checked_sub(unbonding)?;
+        PENDING_WITHDRAWALS.remove(deps.storage, staker.clone());
 
-    Ok((new_ci, curr_block_time))
-}
+        response = response
+            .add_message(send_tokens_msg(&config.stake_denom, &staker, amount)?)
+            .add_attribute("amount_withdrawn", amount);
+    } else {
+        let release_time = env.block.time.plus_seconds(config.unbonding_period);
 
-fn calculate_ci(curr_ci: Uint256, apr: Uint256, time_elapsed: Uint256) -> StdResult<Uint256> {
-    let new_ci =
-        (curr_ci * (ONE + (apr * time_elapsed * ONE) / (SECONDS_IN_YEAR * PERCENTS))) / ONE;
+        let mut withdrawals = withdrawals;
+        withdrawals.push(PendingWithdrawal {
+            amount: staking.staked,
+            release_time,
+        });
 
-    Ok(new_ci)
-}
+        state.total_unbonding = state.total_unbonding.checked_add(staking.staked)?;
+        PENDING_WITHDRAWALS.save(deps.storage, staker.clone(), &withdrawals)?;
 
-fn calculate_reward(tokens_staked: Uint256, ci_last: Uint256, ci_0: Uint256) -> StdResult<Uint256> {
-    let reward = (tokens_staked * (ci_last - ci_0)) / ONE;
+        response = response
+            .add_attribute("amount_unstaked", staking.staked)
+            .add_attribute("release_time", release_time.seconds().to_string());
+    }
 
-    Ok(reward)
-}
+    STATE.save(deps.storage, &state)?;
+    snapshot_stake(deps.storage, &staker, Uint256::zero(), &state, env.block.height)?;
 
-pub fn query_state(deps: Deps) -> StdResult<State> {
-    let state = STATE.load(deps.storage).unwrap();
+    // a broken hook must not be able to trap the principal
+    let hooks = stake_changed_hooks(deps.storage, &staker, staking.staked, Uint256::zero())?
+        .into_iter()
+        .map(|hook| SubMsg::reply_on_error(hook.msg, HOOK_FAILURE_REPLY_ID));
 
-    Ok(state)
+    Ok(
+        response
+            .add_messages(receipt_token_msgs(&config, &staker, Uint256::zero(), receipts)?)
+            .add_submessages(hooks)
+            .add_attribute("user", staker.clone())
+            .add_attribute("method", "execute_emergency_withdraw")
+    )
 }
 
-pub fn query_total_staked(deps: Deps) -> StdResult<Uint256> {
-    let state = STATE.load(deps.storage).unwrap();
+pub fn fund_rewards(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    if info.funds.len() != 1 {
+        return Err(ContractError::InvalidFunds {});
+    }
 
-    Ok(state.total_staked)
+    let coin = &info.funds[0];
+    let amount = Uint256::from_uint128(coin.amount);
+
+    execute_fund_rewards(deps, info.sender, Denom::Native(coin.denom.clone()), amount)
 }
 
-pub fn query_total_withdrawn(deps: Deps) -> StdResult<Uint256> {
-    let state = STATE.load(deps.storage).unwrap();
+fn execute_fund_rewards(
+    deps: DepsMut,
+    funder: Addr,
+    denom: Denom,
+    amount: Uint256
+) -> Result<Response, ContractError> {
+    let mut state = STATE.load(deps.storage)?;
+
+    let stream = state.reward_streams
+        .iter_mut()
+        .find(|stream| stream.denom == denom)
+        .ok_or_else(|| ContractError::InvalidCoin { denom: denom_label(&denom) })?;
 
-    Ok(state.total_withdrawn)
-}
+    stream.reward_reserve = stream.reward_reserve.checked_add(amount)?;
 
-pub fn query_injex_token(deps: Deps) -> StdResult<Addr> {
-    let config = CONFIG.load(deps.storage).unwrap();
+    REWARD_FUNDERS.update(
+        deps.storage,
+        funder.clone(),
+        |funded| -> Result<Vec<RewardAmount>, StdError> {
+            let mut funded = funded.unwrap_or_default();
+
+            match funded.iter_mut().find(|funded| funded.denom == denom) {
+                Some(funded) => {
+                    funded.amount = funded.amount.checked_add(amount)?;
+                }
+                None =>
+                    funded.push(RewardAmount {
+                        denom: denom.clone(),
+                        amount,
+                    }),
+            }
 
-    Ok(Addr::unchecked(config.injex_token))
-}
+            Ok(funded)
+        }
+    )?;
 
-pub fn query_apr(deps: Deps) -> StdResult<Uint256> {
-    let config = CONFIG.load(deps.storage).unwrap();
+    STATE.save(deps.storage, &state)?;
 
-    Ok(config.apr)
+    Ok(
+        Response::new()
+            .add_attribute("funder", funder)
+            .add_attribute("amount_funded", format!("{}{}", amount, denom_label(&denom)))
+            .add_attribute("method", "execute_fund_rewards")
+    )
 }
 
-pub fn query_staker_indo(deps: Deps, user: Addr) -> StdResult<StakerInfo> {
-    let info = USER_STAKINGS.load(deps.storage, user).unwrap();
+/// Takes back reward reserve that is not owed to stakers
+pub fn withdraw_excess_rewards(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    denom: UncheckedDenom,
+    amount: Uint256
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::Treasurer)?;
 
-    Ok(info)
-}
+    let denom = denom.into_checked(deps.as_ref())?;
+    let config = CONFIG.load(deps.storage)?;
+    let mut state = STATE.load(deps.storage)?;
 
-pub fn query_claimable_tokens(deps: Deps, env: Env, user: Addr) -> StdResult<Uint256> {
-    let info = USER_STAKINGS.load(deps.storage, user).unwrap();
-    let config = CONFIG.load(deps.storage).unwrap();
-    let state = STATE.load(deps.storage).unwrap();
+    // liability has to include everything accrued up to now
+    update_ci(deps.storage, &config, &mut state, env.block.time)?;
 
-    let curr_ci = state.ci_current;
-    let apr = config.apr;
-    let curr_block_time = env.block.time;
-    let new_ci = if state.total_staked == Uint256::zero() {
-        state.ci_current
-    } else {
-        let old_block_time = state.ci_time_current;
+    let stream = state.reward_streams
+        .iter_mut()
+        .find(|stream| stream.denom == denom)
+        .ok_or_else(|| ContractError::RewardStreamNotFound { denom: denom_label(&denom) })?;
 
-        calculate_ci(
-            curr_ci,
-            apr,
-            Uint256::from_u128((curr_block_time.seconds() - old_block_time.seconds()).into())
-        ).unwrap()
-    };
+    if amount > stream.reward_reserve.saturating_sub(stream.total_liability) {
+        return Err(ContractError::ExcessRewardsExceeded {});
+    }
 
-    let reward = calculate_reward(info.staked, new_ci, info.ci_0).unwrap() + info.reward;
+    stream.reward_reserve = stream.reward_reserve.checked_sub(amount)?;
 
-    Ok(reward)
+    STATE.save(deps.storage, &state)?;
+
+    Ok(
+        Response::new()
+            .add_message(send_tokens_msg(&denom, &info.sender, amount)?)
+            .add_attribute("amount_withdrawn", format!("{}{}", amount, denom_label(&denom)))
+            .add_attribute("method", "execute_withdraw_excess_rewards")
+    )
+}
+
+pub fn claim_rewards(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo
+) -> Result<Response, ContractError> {
+    ensure_not_paused(deps.as_ref())?;
+
+    let config = CONFIG.load(deps.storage)?;
+    let mut state = STATE.load(deps.storage)?;
+
+    let mut staking = USER_STAKINGS.may_load(deps.storage, info.sender.clone())?.unwrap_or_else(
+        || new_staker_info(Timestamp::from_seconds(0))
+    );
+
+    let expired = update_ci(deps.storage, &config, &mut state, env.block.time)?;
+
+    let old_staked = staking.staked;
+    let old_boost = lock_boost(&staking)?;
+
+    update_rewards(deps.storage, &config, &mut staking, &state, &expired)?;
+
+    let old_weight = get_weight(&staking)?;
+
+    let compounded = auto_compound(deps.as_ref(), &config, &mut state, &mut staking)?;
+
+    update_total_weight(&mut state, old_weight, get_weight(&staking)?)?;
+    update_staker_count(&mut state, old_staked, staking.staked);
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut response = Response::new();
+
+    for (stream, staker_reward) in state.reward_streams
+        .iter_mut()
+        .zip(staking.rewards.iter_mut()) {
+        let reward = staker_reward.reward;
+
+        if reward == Uint256::zero() {
+            continue;
+        }
+
+        // paying out while other stakers' accrued rewards are unbacked would leave them unpaid
+        ensure_reserve_covers_liability(stream)?;
+
+        messages.push(send_tokens_msg(&stream.denom, &info.sender, reward)?);
+        response = response.add_attribute(
+            "amount_claimed",
+            format!("{}{}", reward, denom_label(&stream.denom))
+        );
+
+        stream.total_withdrawn = stream.total_withdrawn.checked_add(reward)?;
+        stream.total_liability = stream.total_liability.saturating_sub(reward);
+        stream.reward_reserve = stream.reward_reserve.checked_sub(reward)?;
+        staker_reward.reward = Uint256::zero();
+    }
+
+    if messages.is_empty() && compounded == Uint256::zero() {
+        return Err(ContractError::CannotClaim {});
+    }
+
+    update_lock_expiry(deps.storage, old_boost, lock_boost(&staking)?)?;
+    USER_STAKINGS.save(deps.storage, info.sender.clone(), &staking)?;
+    STATE.save(deps.storage, &state)?;
+    snapshot_stake(deps.storage, &info.sender, staking.staked, &state, env.block.height)?;
+
+    messages.extend(receipt_token_msgs(&config, &info.sender, compounded, Uint256::zero())?);
+
+    let hooks = stake_changed_hooks(deps.storage, &info.sender, old_staked, staking.staked)?;
+
+    Ok(
+        response
+            .add_messages(messages)
+            .add_submessages(hooks)
+            .add_attribute("user", info.sender.clone())
+            .add_attribute("amount_compounded", compounded)
+            .add_attribute("method", "execute_claim")
+    )
+}
+
+pub fn compound(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    ensure_not_paused(deps.as_ref())?;
+
+    let config = CONFIG.load(deps.storage)?;
+    let mut state = STATE.load(deps.storage)?;
+
+    if !state.reward_streams.iter().any(|stream| stream.denom == config.stake_denom) {
+        return Err(ContractError::CompoundUnavailable {});
+    }
+
+    let mut staking = USER_STAKINGS.may_load(deps.storage, info.sender.clone())?.unwrap_or_else(
+        || new_staker_info(Timestamp::from_seconds(0))
+    );
+
+    let expired = update_ci(deps.storage, &config, &mut state, env.block.time)?;
+
+    let old_staked = staking.staked;
+    let old_boost = lock_boost(&staking)?;
+
+    update_rewards(deps.storage, &config, &mut staking, &state, &expired)?;
+
+    let old_weight = get_weight(&staking)?;
+
+    let compounded = compound_rewards(&config, &mut state, &mut staking)?;
+
+    if compounded == Uint256::zero() {
+        return Err(ContractError::CannotClaim {});
+    }
+
+    update_total_weight(&mut state, old_weight, get_weight(&staking)?)?;
+    update_staker_count(&mut state, old_staked, staking.staked);
+
+    update_lock_expiry(deps.storage, old_boost, lock_boost(&staking)?)?;
+    USER_STAKINGS.save(deps.storage, info.sender.clone(), &staking)?;
+    STATE.save(deps.storage, &state)?;
+    snapshot_stake(deps.storage, &info.sender, staking.staked, &state, env.block.height)?;
+
+    let receipt_msgs = receipt_token_msgs(&config, &info.sender, compounded, Uint256::zero())?;
+    let hooks = stake_changed_hooks(deps.storage, &info.sender, old_staked, staking.staked)?;
+
+    Ok(
+        Response::new()
+            .add_messages(receipt_msgs)
+            .add_submessages(hooks)
+            .add_attribute("user", info.sender.clone())
+            .add_attribute("amount_compounded", compounded)
+            .add_attribute("method", "execute_compound")
+    )
+}
+
+pub fn set_auto_compound(
+    deps: DepsMut,
+    info: MessageInfo,
+    enabled: bool
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let state = STATE.load(deps.storage)?;
+
+    if enabled && !state.reward_streams.iter().any(|stream| stream.denom == config.stake_denom) {
+        return Err(ContractError::CompoundUnavailable {});
+    }
+
+    // an empty entry would list the address as a staker
+    let mut staking = USER_STAKINGS.may_load(deps.storage, info.sender.clone())?.ok_or(
+        ContractError::NoPosition {}
+    )?;
+
+    staking.auto_compound = enabled;
+
+    USER_STAKINGS.save(deps.storage, info.sender.clone(), &staking)?;
+
+    Ok(
+        Response::new()
+            .add_attribute("user", info.sender.clone())
+            .add_attribute("auto_compound", enabled.to_string())
+            .add_attribute("method", "execute_set_auto_compound")
+    )
+}
+
+pub fn add_reward_stream(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    denom: UncheckedDenom,
+    apr: Decimal256,
+    emission_rate: Uint256
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::Owner)?;
+
+    let config = CONFIG.load(deps.storage)?;
+
+    validate_stream_rate(&config, apr, emission_rate)?;
+
+    let denom = denom.into_checked(deps.as_ref())?;
+    let mut state = STATE.load(deps.storage)?;
+
+    update_ci(deps.storage, &config, &mut state, env.block.time)?;
+
+    match state.reward_streams.iter_mut().find(|stream| stream.denom == denom) {
+        Some(stream) if stream.active => {
+            return Err(ContractError::RewardStreamExists {});
+        }
+        // a removed stream keeps its frozen index, so re-adding it resumes accrual from now on
+        Some(stream) => {
+            stream.apr = apr;
+            stream.emission_rate = emission_rate;
+            stream.emission_end = None;
+            stream.active = true;
+        }
+        None => {
+            state.reward_streams.push(RewardStream {
+                denom: denom.clone(),
+                apr,
+                emission_rate,
+                emission_end: None,
+                ci_current: ONE,
+                total_withdrawn: Uint256::zero(),
+                total_liability: Uint256::zero(),
+                reward_reserve: Uint256::zero(),
+                apr_schedule: vec![],
+                active: true,
+            });
+        }
+    }
+
+    STATE.save(deps.storage, &state)?;
+
+    Ok(
+        Response::new()
+            .add_attribute("denom", denom_label(&denom))
+            .add_attribute("apr", apr.to_string())
+            .add_attribute("emission_rate", emission_rate)
+            .add_attribute("method", "execute_add_reward_stream")
+    )
+}
+
+pub fn remove_reward_stream(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    denom: UncheckedDenom
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::Owner)?;
+
+    let denom = denom.into_checked(deps.as_ref())?;
+    let config = CONFIG.load(deps.storage)?;
+    let mut state = STATE.load(deps.storage)?;
+
+    update_ci(deps.storage, &config, &mut state, env.block.time)?;
+
+    let stream = state.reward_streams
+        .iter_mut()
+        .find(|stream| stream.active && stream.denom == denom)
+        .ok_or_else(|| ContractError::RewardStreamNotFound { denom: denom_label(&denom) })?;
+
+    stream.active = false;
+    stream.apr_schedule.clear();
+
+    STATE.save(deps.storage, &state)?;
+
+    Ok(
+        Response::new()
+            .add_attribute("denom", denom_label(&denom))
+            .add_attribute("method", "execute_remove_reward_stream")
+    )
+}
+
+pub fn change_apr(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    denom: UncheckedDenom,
+    new_apr: Decimal256
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::RateManager)?;
+
+    ensure_reward_mode(deps.as_ref(), RewardMode::Apr)?;
+
+    if new_apr.is_zero() {
+        return Err(ContractError::InvalidApr {});
+    }
+
+    let denom = denom.into_checked(deps.as_ref())?;
+    let config = CONFIG.load(deps.storage)?;
+    let mut state = STATE.load(deps.storage)?;
+
+    update_ci(deps.storage, &config, &mut state, env.block.time)?;
+
+    let stream = state.reward_streams
+        .iter_mut()
+        .find(|stream| stream.active && stream.denom == denom)
+        .ok_or_else(|| ContractError::RewardStreamNotFound { denom: denom_label(&denom) })?;
+
+    stream.apr = new_apr;
+
+    STATE.save(deps.storage, &state)?;
+
+    Ok(
+        Response::new()
+            .add_attribute("new_apr", new_apr.to_string())
+            .add_attribute("method", "execute_new_apr")
+    )
+}
+
+pub fn change_emission_rate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    denom: UncheckedDenom,
+    emission_rate: Uint256
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::RateManager)?;
+    ensure_reward_mode(deps.as_ref(), RewardMode::Emission)?;
+
+    if emission_rate == Uint256::zero() {
+        return Err(ContractError::InvalidEmissionRate {});
+    }
+
+    let denom = denom.into_checked(deps.as_ref())?;
+    let config = CONFIG.load(deps.storage)?;
+    let mut state = STATE.load(deps.storage)?;
+
+    update_ci(deps.storage, &config, &mut state, env.block.time)?;
+
+    let stream = state.reward_streams
+        .iter_mut()
+        .find(|stream| stream.active && stream.denom == denom)
+        .ok_or_else(|| ContractError::RewardStreamNotFound { denom: denom_label(&denom) })?;
+
+    stream.emission_rate = emission_rate;
+    stream.emission_end = None;
+
+    STATE.save(deps.storage, &state)?;
+
+    Ok(
+        Response::new()
+            .add_attribute("emission_rate", emission_rate)
+            .add_attribute("method", "execute_change_emission_rate")
+    )
+}
+
+/// Spreads `amount` evenly over the next `duration` seconds, emission stops afterwards
+pub fn set_emission_budget(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    denom: UncheckedDenom,
+    amount: Uint256,
+    duration: u64
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::RateManager)?;
+    ensure_reward_mode(deps.as_ref(), RewardMode::Emission)?;
+
+    if duration == 0 {
+        return Err(ContractError::InvalidEmissionRate {});
+    }
+
+    let emission_rate = amount / Uint256::from_u128(duration.into());
+
+    if emission_rate == Uint256::zero() {
+        return Err(ContractError::InvalidEmissionRate {});
+    }
+
+    let denom = denom.into_checked(deps.as_ref())?;
+    let config = CONFIG.load(deps.storage)?;
+    let mut state = STATE.load(deps.storage)?;
+
+    update_ci(deps.storage, &config, &mut state, env.block.time)?;
+
+    let stream = state.reward_streams
+        .iter_mut()
+        .find(|stream| stream.active && stream.denom == denom)
+        .ok_or_else(|| ContractError::RewardStreamNotFound { denom: denom_label(&denom) })?;
+
+    let emission_end = env.block.time.plus_seconds(duration);
+
+    stream.emission_rate = emission_rate;
+    stream.emission_end = Some(emission_end);
+
+    STATE.save(deps.storage, &state)?;
+
+    Ok(
+        Response::new()
+            .add_attribute("emission_rate", emission_rate)
+            .add_attribute("emission_end", emission_end.seconds().to_string())
+            .add_attribute("method", "execute_set_emission_budget")
+    )
+}
+
+pub fn schedule_apr_change(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    denom: UncheckedDenom,
+    effective_time: Timestamp,
+    apr: Decimal256
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::RateManager)?;
+
+    ensure_reward_mode(deps.as_ref(), RewardMode::Apr)?;
+
+    if apr.is_zero() {
+        return Err(ContractError::InvalidApr {});
+    }
+
+    if effective_time <= env.block.time {
+        return Err(ContractError::InvalidEffectiveTime {});
+    }
+
+    let denom = denom.into_checked(deps.as_ref())?;
+    let config = CONFIG.load(deps.storage)?;
+    let mut state = STATE.load(deps.storage)?;
+
+    update_ci(deps.storage, &config, &mut state, env.block.time)?;
+
+    let stream = state.reward_streams
+        .iter_mut()
+        .find(|stream| stream.active && stream.denom == denom)
+        .ok_or_else(|| ContractError::RewardStreamNotFound { denom: denom_label(&denom) })?;
+
+    // the schedule stays sorted so accrual can walk it in order
+    let position = stream.apr_schedule.partition_point(
+        |change| change.effective_time < effective_time
+    );
+
+    let exists = stream.apr_schedule
+        .get(position)
+        .is_some_and(|change| change.effective_time == effective_time);
+
+    if exists {
+        return Err(ContractError::AprChangeExists {});
+    }
+
+    stream.apr_schedule.insert(position, AprChange { effective_time, apr });
+
+    STATE.save(deps.storage, &state)?;
+
+    Ok(
+        Response::new()
+            .add_attribute("denom", denom_label(&denom))
+            .add_attribute("effective_time", effective_time.seconds().to_string())
+            .add_attribute("new_apr", apr.to_string())
+            .add_attribute("method", "execute_schedule_apr_change")
+    )
+}
+
+pub fn cancel_apr_change(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    denom: UncheckedDenom,
+    effective_time: Timestamp
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::RateManager)?;
+
+    let denom = denom.into_checked(deps.as_ref())?;
+    let config = CONFIG.load(deps.storage)?;
+    let mut state = STATE.load(deps.storage)?;
+
+    // changes that took effect already are applied here and can no longer be cancelled
+    update_ci(deps.storage, &config, &mut state, env.block.time)?;
+
+    let stream = state.reward_streams
+        .iter_mut()
+        .find(|stream| stream.active && stream.denom == denom)
+        .ok_or_else(|| ContractError::RewardStreamNotFound { denom: denom_label(&denom) })?;
+
+    let position = stream.apr_schedule
+        .iter()
+        .position(|change| change.effective_time == effective_time)
+        .ok_or(ContractError::AprChangeNotFound {})?;
+
+    stream.apr_schedule.remove(position);
+
+    STATE.save(deps.storage, &state)?;
+
+    Ok(
+        Response::new()
+            .add_attribute("denom", denom_label(&denom))
+            .add_attribute("effective_time", effective_time.seconds().to_string())
+            .add_attribute("method", "execute_cancel_apr_change")
+    )
+}
+
+/// Pushes the end of the reward program back, accrual resumes from now if it had ended
+pub fn extend_rewards_end(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    rewards_end: Timestamp
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::Owner)?;
+
+    let mut config = CONFIG.load(deps.storage)?;
+    let mut state = STATE.load(deps.storage)?;
+
+    // settle accrual under the old end before moving it
+    update_ci(deps.storage, &config, &mut state, env.block.time)?;
+
+    let invalid_end = match config.rewards_end {
+        Some(current_end) => rewards_end <= current_end || rewards_end <= env.block.time,
+        // an open-ended program can be given an end from now on
+        None =>
+            rewards_end < env.block.time ||
+                config.rewards_start.is_some_and(|start| rewards_end <= start),
+    };
+
+    if invalid_end {
+        return Err(ContractError::InvalidRewardsWindow {});
+    }
+
+    config.rewards_end = Some(rewards_end);
+
+    CONFIG.save(deps.storage, &config)?;
+    STATE.save(deps.storage, &state)?;
+
+    Ok(
+        Response::new()
+            .add_attribute("rewards_end", rewards_end.seconds().to_string())
+            .add_attribute("method", "execute_extend_rewards_end")
+    )
+}
+
+pub fn change_stake_denom(
+    deps: DepsMut,
+    info: MessageInfo,
+    new_stake_denom: UncheckedDenom
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::Owner)?;
+
+    let new_stake_denom = new_stake_denom.into_checked(deps.as_ref())?;
+
+    let state = STATE.load(deps.storage)?;
+    let outstanding = state.total_staked.checked_add(state.total_unbonding)?;
+
+    // principal already deposited is paid back in the denom it came in
+    if !outstanding.is_zero() {
+        return Err(ContractError::StakeDenomInUse { amount: outstanding });
+    }
+
+    CONFIG.update(
+        deps.storage,
+        |mut config| -> Result<Config, StdError> {
+            config.stake_denom = new_stake_denom;
+
+            Ok(config)
+        }
+    )?;
+
+    Ok(Response::new().add_attribute("method", "execute_new_stake_denom"))
+}
+
+pub fn propose_admin(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    address: String,
+    expiry: Option<Expiration>
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::Owner)?;
+
+    let expiry = expiry.unwrap_or_default();
+
+    if expiry.is_expired(&env.block) {
+        return Err(ContractError::AdminProposalExpired {});
+    }
+
+    let pending_admin = PendingAdmin {
+        address: deps.api.addr_validate(&address)?,
+        expiry,
+    };
+
+    PENDING_ADMIN.save(deps.storage, &pending_admin)?;
+
+    Ok(
+        Response::new()
+            .add_attribute("pending_admin", pending_admin.address)
+            .add_attribute("expiry", pending_admin.expiry.to_string())
+            .add_attribute("method", "execute_propose_admin")
+    )
+}
+
+pub fn accept_admin(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    let pending_admin = PENDING_ADMIN.may_load(deps.storage)?.ok_or(
+        ContractError::NoPendingAdmin {}
+    )?;
+
+    if pending_admin.address != info.sender {
+        return Err(ContractError::OnlyPendingAdmin {});
+    }
+
+    if pending_admin.expiry.is_expired(&env.block) {
+        return Err(ContractError::AdminProposalExpired {});
+    }
+
+    ADMIN.save(deps.storage, &pending_admin.address)?;
+    PENDING_ADMIN.remove(deps.storage);
+
+    Ok(
+        Response::new()
+            .add_attribute("admin", pending_admin.address)
+            .add_attribute("method", "execute_accept_admin")
+    )
+}
+
+pub fn cancel_admin_proposal(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::Owner)?;
+
+    if PENDING_ADMIN.may_load(deps.storage)?.is_none() {
+        return Err(ContractError::NoPendingAdmin {});
+    }
+
+    PENDING_ADMIN.remove(deps.storage);
+
+    Ok(Response::new().add_attribute("method", "execute_cancel_admin_proposal"))
+}
+
+/// Leaves the contract without an admin, every admin-only message fails from then on
+pub fn renounce_admin(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::Owner)?;
+
+    ADMIN.remove(deps.storage);
+    PENDING_ADMIN.remove(deps.storage);
+
+    Ok(Response::new().add_attribute("method", "execute_renounce_admin"))
+}
+
+pub fn change_unbonding_period(
+    deps: DepsMut,
+    info: MessageInfo,
+    new_unbonding_period: u64
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::Owner)?;
+
+    CONFIG.update(
+        deps.storage,
+        |mut config| -> Result<Config, StdError> {
+            config.unbonding_period = new_unbonding_period;
+
+            Ok(config)
+        }
+    )?;
+
+    Ok(
+        Response::new()
+            .add_attribute("new_unbonding_period", new_unbonding_period.to_string())
+            .add_attribute("method", "execute_new_unbonding_period")
+    )
+}
+
+pub fn update_lock_tiers(
+    deps: DepsMut,
+    info: MessageInfo,
+    lock_tiers: Vec<LockTier>
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::Owner)?;
+
+    validate_lock_tiers(&lock_tiers)?;
+
+    // existing positions keep the multiplier they locked in until their lock ends
+    CONFIG.update(
+        deps.storage,
+        |mut config| -> Result<Config, StdError> {
+            config.lock_tiers = lock_tiers;
+
+            Ok(config)
+        }
+    )?;
+
+    Ok(Response::new().add_attribute("method", "execute_update_lock_tiers"))
+}
+
+pub fn update_staking_caps(
+    deps: DepsMut,
+    info: MessageInfo,
+    max_total_staked: Option<Uint256>,
+    min_stake_amount: Option<Uint256>,
+    max_stake_per_user: Option<Uint256>
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::Owner)?;
+
+    validate_staking_caps(min_stake_amount, max_stake_per_user)?;
+
+    // existing positions above a lowered cap stay as they are, only new deposits are checked
+    CONFIG.update(
+        deps.storage,
+        |mut config| -> Result<Config, StdError> {
+            config.max_total_staked = max_total_staked;
+            config.min_stake_amount = min_stake_amount;
+            config.max_stake_per_user = max_stake_per_user;
+
+            Ok(config)
+        }
+    )?;
+
+    Ok(Response::new().add_attribute("method", "execute_update_staking_caps"))
+}
+
+pub fn grant_role(
+    deps: DepsMut,
+    info: MessageInfo,
+    role: Role,
+    address: String
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::Owner)?;
+
+    // ownership only moves through `ProposeAdmin`
+    if role == Role::Owner {
+        return Err(ContractError::OwnerNotGrantable {});
+    }
+
+    let address = deps.api.addr_validate(&address)?;
+
+    ROLES.save(deps.storage, (role.as_str(), address.clone()), &Empty {})?;
+
+    Ok(
+        Response::new()
+            .add_attribute("role", role.as_str())
+            .add_attribute("address", address)
+            .add_attribute("method", "execute_grant_role")
+    )
+}
+
+pub fn revoke_role(
+    deps: DepsMut,
+    info: MessageInfo,
+    role: Role,
+    address: String
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::Owner)?;
+
+    if role == Role::Owner {
+        return Err(ContractError::OwnerNotGrantable {});
+    }
+
+    let address = deps.api.addr_validate(&address)?;
+
+    if !ROLES.has(deps.storage, (role.as_str(), address.clone())) {
+        return Err(ContractError::MissingRole { role, address: address.to_string() });
+    }
+
+    ROLES.remove(deps.storage, (role.as_str(), address.clone()));
+
+    Ok(
+        Response::new()
+            .add_attribute("role", role.as_str())
+            .add_attribute("address", address)
+            .add_attribute("method", "execute_revoke_role")
+    )
+}
+
+pub fn add_hook(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::Owner)?;
+
+    let address = deps.api.addr_validate(&address)?;
+
+    if HOOKS.has(deps.storage, address.clone()) {
+        return Err(ContractError::HookAlreadyRegistered { address: address.to_string() });
+    }
+
+    HOOKS.save(deps.storage, address.clone(), &Empty {})?;
+
+    Ok(
+        Response::new()
+            .add_attribute("hook", address)
+            .add_attribute("method", "execute_add_hook")
+    )
+}
+
+pub fn remove_hook(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::Owner)?;
+
+    let address = deps.api.addr_validate(&address)?;
+
+    if !HOOKS.has(deps.storage, address.clone()) {
+        return Err(ContractError::HookNotRegistered { address: address.to_string() });
+    }
+
+    HOOKS.remove(deps.storage, address.clone());
+
+    Ok(
+        Response::new()
+            .add_attribute("hook", address)
+            .add_attribute("method", "execute_remove_hook")
+    )
+}
+
+pub fn pause(
+    deps: DepsMut,
+    info: MessageInfo,
+    reason: Option<String>
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::Pauser)?;
+
+    PAUSE.save(deps.storage, &(PauseInfo {
+        paused: true,
+        reason: reason.clone(),
+    }))?;
+
+    Ok(
+        Response::new()
+            .add_attribute("reason", reason.unwrap_or_default())
+            .add_attribute("method", "execute_pause")
+    )
+}
+
+pub fn unpause(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::Pauser)?;
+
+    PAUSE.save(deps.storage, &PauseInfo::default())?;
+
+    Ok(Response::new().add_attribute("method", "execute_unpause"))
+}
+
+/// Single permission check for privileged messages, the owner passes every role
+fn ensure_role(deps: Deps, sender: &Addr, role: Role) -> Result<(), ContractError> {
+    // a renounced contract has no owner left to match
+    if ADMIN.may_load(deps.storage)?.as_ref() == Some(sender) {
+        return Ok(());
+    }
+
+    if role != Role::Owner && ROLES.has(deps.storage, (role.as_str(), sender.clone())) {
+        return Ok(());
+    }
+
+    Err(ContractError::MissingRole { role, address: sender.to_string() })
+}
+
+fn ensure_not_paused(deps: Deps) -> Result<(), ContractError> {
+    if PAUSE.load(deps.storage)?.paused {
+        return Err(ContractError::Paused {});
+    }
+
+    Ok(())
+}
+
+fn validate_stream_rate(
+    config: &Config,
+    apr: Decimal256,
+    emission_rate: Uint256
+) -> Result<(), ContractError> {
+    match config.reward_mode {
+        RewardMode::Apr if apr.is_zero() => Err(ContractError::InvalidApr {}),
+        RewardMode::Emission if emission_rate == Uint256::zero() => {
+            Err(ContractError::InvalidEmissionRate {})
+        }
+        _ => Ok(()),
+    }
+}
+
+fn ensure_reward_mode(deps: Deps, reward_mode: RewardMode) -> Result<(), ContractError> {
+    if CONFIG.load(deps.storage)?.reward_mode != reward_mode {
+        return Err(ContractError::InvalidRewardMode {});
+    }
+
+    Ok(())
+}
+
+fn ensure_stake_started(config: &Config, block_time: Timestamp) -> Result<(), ContractError> {
+    let not_started = config.rewards_start.is_some_and(|start| block_time < start);
+
+    if config.stake_requires_start && not_started {
+        return Err(ContractError::RewardsNotStarted {});
+    }
+
+    Ok(())
+}
+
+fn ensure_can_unstake(
+    staking: &StakerInfo,
+    amount: Uint256,
+    block_time: Timestamp
+) -> Result<(), ContractError> {
+    if staking.staked == Uint256::zero() {
+        return Err(ContractError::CannotUnstake {});
+    } else if staking.staked < amount {
+        return Err(ContractError::CannotUnstakeAmount { amount, staked: staking.staked });
+    } else if staking.lock_end > block_time {
+        return Err(ContractError::Locked { until: staking.lock_end.seconds() });
+    }
+
+    Ok(())
+}
+
+/// A position is either closed completely or stays above the minimum
+fn validate_remaining_stake(config: &Config, staking: &StakerInfo) -> Result<(), ContractError> {
+    if let Some(limit) = config.min_stake_amount {
+        if staking.staked != Uint256::zero() && staking.staked < limit {
+            return Err(ContractError::StakeBelowMinimum { amount: staking.staked, limit });
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks the position and pool after a deposit, the `compounded` rewards rolled in with it
+/// are left out of the caps like on every other compounding path
+fn validate_stake_caps(
+    config: &Config,
+    state: &State,
+    staking: &StakerInfo,
+    compounded: Uint256
+) -> Result<(), ContractError> {
+    if let Some(limit) = config.max_total_staked {
+        let amount = state.total_staked.checked_sub(compounded)?;
+
+        if amount > limit {
+            return Err(ContractError::TotalStakeCapExceeded { amount, limit });
+        }
+    }
+
+    validate_position_caps(config, staking, compounded)
+}
+
+/// Checks a single position against the minimum and the per-user cap
+fn validate_position_caps(
+    config: &Config,
+    staking: &StakerInfo,
+    compounded: Uint256
+) -> Result<(), ContractError> {
+    if let Some(limit) = config.min_stake_amount {
+        if staking.staked < limit {
+            return Err(ContractError::StakeBelowMinimum { amount: staking.staked, limit });
+        }
+    }
+
+    if let Some(limit) = config.max_stake_per_user {
+        let amount = staking.staked.checked_sub(compounded)?;
+
+        if amount > limit {
+            return Err(ContractError::UserStakeCapExceeded { amount, limit });
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_staking_caps(
+    min_stake_amount: Option<Uint256>,
+    max_stake_per_user: Option<Uint256>
+) -> Result<(), ContractError> {
+    if let (Some(min), Some(max)) = (min_stake_amount, max_stake_per_user) {
+        if min > max {
+            return Err(ContractError::InvalidStakingCaps {});
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_lock_tiers(lock_tiers: &[LockTier]) -> Result<(), ContractError> {
+    for (i, tier) in lock_tiers.iter().enumerate() {
+        let duplicate = lock_tiers[..i].iter().any(|other| other.duration == tier.duration);
+
+        let multiplier_out_of_range =
+            tier.multiplier < PERCENTS || tier.multiplier > MAX_LOCK_MULTIPLIER;
+
+        if tier.duration == 0 || multiplier_out_of_range || duplicate {
+            return Err(ContractError::InvalidLockTier {});
+        }
+    }
+
+    Ok(())
+}
+
+/// Adds `amount` to the position, a new lock boosts the whole position
+fn apply_lock(
+    config: &Config,
+    staking: &mut StakerInfo,
+    amount: Uint256,
+    lock_duration: Option<u64>,
+    block_time: Timestamp
+) -> Result<(), ContractError> {
+    let Some(lock_duration) = lock_duration else {
+        return Ok(add_stake(staking, amount)?);
+    };
+
+    let multiplier = get_lock_multiplier(config, lock_duration)?;
+
+    // a running lock can only be extended into an equal or better tier
+    if staking.lock_end > block_time && multiplier < staking.multiplier {
+        return Err(ContractError::InvalidLockTier {});
+    }
+
+    staking.staked = staking.staked.checked_add(amount)?;
+    staking.multiplier = multiplier;
+    staking.lock_end = staking.lock_end.max(block_time.plus_seconds(lock_duration));
+
+    Ok(())
+}
+
+/// Adds `amount` without locking it, a running lock spreads the boost it holds over the larger
+/// stake so tokens joining late do not earn a tier they were never locked for
+fn add_stake(staking: &mut StakerInfo, amount: Uint256) -> StdResult<()> {
+    let weight = get_weight(staking)?.checked_add(amount)?;
+
+    staking.staked = staking.staked.checked_add(amount)?;
+
+    if staking.multiplier != PERCENTS && !staking.staked.is_zero() {
+        staking.multiplier = mul_div_floor(weight, PERCENTS, staking.staked)?;
+    }
+
+    Ok(())
+}
+
+fn get_lock_multiplier(config: &Config, lock_duration: u64) -> Result<Uint256, ContractError> {
+    if lock_duration == 0 {
+        return Ok(PERCENTS);
+    }
+
+    config.lock_tiers
+        .iter()
+        .find(|tier| tier.duration == lock_duration)
+        .map(|tier| tier.multiplier)
+        .ok_or(ContractError::InvalidLockTier {})
+}
+
+/// Rolls the staker's settled rewards in the staking asset into principal
+fn compound_rewards(
+    config: &Config,
+    state: &mut State,
+    staking: &mut StakerInfo
+) -> Result<Uint256, ContractError> {
+    let Some((stream, staker_reward)) = state.reward_streams
+        .iter_mut()
+        .zip(staking.rewards.iter_mut())
+        .find(|(stream, _)| stream.denom == config.stake_denom) else {
+        return Ok(Uint256::zero());
+    };
+
+    let reward = staker_reward.reward;
+
+    if reward == Uint256::zero() {
+        return Ok(reward);
+    }
+
+    // compounded rewards become principal, so the reserve has to back them like a claim
+    ensure_reserve_covers_liability(stream)?;
+
+    stream.total_withdrawn = stream.total_withdrawn.checked_add(reward)?;
+    stream.total_liability = stream.total_liability.saturating_sub(reward);
+    stream.reward_reserve = stream.reward_reserve.checked_sub(reward)?;
+    staker_reward.reward = Uint256::zero();
+
+    add_stake(staking, reward)?;
+    state.total_staked = state.total_staked.checked_add(reward)?;
+
+    Ok(reward)
+}
+
+/// Compounds for stakers that opted in, an underfunded reserve or a pause leaves rewards in place
+fn auto_compound(
+    deps: Deps,
+    config: &Config,
+    state: &mut State,
+    staking: &mut StakerInfo
+) -> Result<Uint256, ContractError> {
+    if !staking.auto_compound || PAUSE.load(deps.storage)?.paused {
+        return Ok(Uint256::zero());
+    }
+
+    match compound_rewards(config, state, staking) {
+        Err(ContractError::InsufficientContractBalance { .. }) => Ok(Uint256::zero()),
+        res => res,
+    }
+}
+
+fn new_staker_info(block_time: Timestamp) -> StakerInfo {
+    StakerInfo {
+        block_time,
+        staked: Uint256::zero(),
+        multiplier: PERCENTS,
+        lock_end: Timestamp::from_seconds(0),
+        auto_compound: false,
+        rewards: vec![],
+    }
+}
+
+/// Claimable rewards at `time` if nobody interacts with the contract until then, `pending` holds
+/// the locks ending before it
+fn project_rewards(
+    storage: &dyn Storage,
+    config: &Config,
+    state: &State,
+    staking: &StakerInfo,
+    time: Timestamp,
+    pending: &BTreeMap<u64, Uint256>
+) -> StdResult<Vec<RewardAmount>> {
+    let mut state = state.clone();
+    let mut staking = staking.clone();
+
+    let expired = advance_ci(config, &mut state, time, pending)?;
+    update_rewards(storage, config, &mut staking, &state, &expired)?;
+
+    Ok(reward_amounts(&state, &staking))
+}
+
+fn simulation_response(
+    storage: &dyn Storage,
+    config: &Config,
+    state: &State,
+    staking: &StakerInfo,
+    block_time: Timestamp,
+    release_time: Option<Timestamp>,
+    pending: &BTreeMap<u64, Uint256>
+) -> StdResult<SimulationResponse> {
+    let rewards = reward_amounts(state, staking);
+    let projected = project_rewards(
+        storage,
+        config,
+        state,
+        staking,
+        block_time.plus_seconds(SECONDS_IN_YEAR),
+        pending
+    )?;
+
+    let projected_yearly_rewards = projected
+        .into_iter()
+        .zip(rewards.iter())
+        .map(|(projected, current)| -> StdResult<RewardAmount> {
+            Ok(RewardAmount {
+                denom: projected.denom,
+                amount: projected.amount.checked_sub(current.amount)?,
+            })
+        })
+        .collect::<StdResult<Vec<RewardAmount>>>()?;
+
+    Ok(SimulationResponse {
+        staked: staking.staked,
+        total_staked: state.total_staked,
+        rewards,
+        projected_yearly_rewards,
+        release_time,
+    })
+}
+
+/// Stored rewards of `staking` paired with their stream denoms
+fn reward_amounts(state: &State, staking: &StakerInfo) -> Vec<RewardAmount> {
+    state.reward_streams
+        .iter()
+        .zip(staking.rewards.iter())
+        .map(|(stream, staker_reward)| RewardAmount {
+            denom: stream.denom.clone(),
+            amount: staker_reward.reward,
+        })
+        .collect()
+}
+
+/// Queries return `StdError`, contract errors keep their message
+fn to_std_error(err: ContractError) -> StdError {
+    match err {
+        ContractError::Std(err) => err,
+        err => StdError::generic_err(err.to_string()),
+    }
+}
+
+/// Records the position and pool after a change to `staked`, read back by height in
+/// `StakedBalanceAtHeight` and `TotalStakedAtHeight`
+fn snapshot_stake(
+    storage: &mut dyn Storage,
+    staker: &Addr,
+    staked: Uint256,
+    state: &State,
+    height: u64
+) -> StdResult<()> {
+    STAKED_BALANCES.save(storage, staker.clone(), &staked, height)?;
+    STAKED_TOTAL.save(storage, &state.total_staked, height)
+}
+
+/// Keeps the receipt token supply equal to `State.total_staked`, mints `minted` to the staker and
+/// burns `burned` tokens the contract was sent back
+fn receipt_token_msgs(
+    config: &Config,
+    staker: &Addr,
+    minted: Uint256,
+    burned: Uint256
+) -> StdResult<Vec<CosmosMsg>> {
+    let receipt_token = match &config.receipt_token {
+        Some(receipt_token) => receipt_token,
+        None => {
+            return Ok(vec![]);
+        }
+    };
+
+    let mut msgs = vec![];
+
+    if !minted.is_zero() {
+        msgs.push(
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: receipt_token.to_string(),
+                msg: to_json_binary(
+                    &(ReceiptTokenExecuteMsg::Mint {
+                        recipient: staker.to_string(),
+                        amount: Uint128::try_from(minted)?,
+                    })
+                )?,
+                funds: vec![],
+            })
+        );
+    }
+
+    if !burned.is_zero() {
+        msgs.push(
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: receipt_token.to_string(),
+                msg: to_json_binary(
+                    &(ReceiptTokenExecuteMsg::Burn {
+                        amount: Uint128::try_from(burned)?,
+                    })
+                )?,
+                funds: vec![],
+            })
+        );
+    }
+
+    Ok(msgs)
+}
+
+/// `StakeChangedHook` for every registered hook, a failing hook reverts the whole transaction
+fn stake_changed_hooks(
+    storage: &dyn Storage,
+    addr: &Addr,
+    old_staked: Uint256,
+    new_staked: Uint256
+) -> StdResult<Vec<SubMsg>> {
+    let msg = to_json_binary(
+        &(StakeChangedHookMsg::StakeChangedHook {
+            addr: addr.clone(),
+            old_staked,
+            new_staked,
+        })
+    )?;
+
+    HOOKS.keys(storage, None, None, Order::Ascending)
+        .map(|hook| {
+            Ok(
+                SubMsg::new(WasmMsg::Execute {
+                    contract_addr: hook?.to_string(),
+                    msg: msg.clone(),
+                    funds: vec![],
+                })
+            )
+        })
+        .collect()
+}
+
+/// Keeps `State.staker_count` in line with the positions that hold a stake
+fn update_staker_count(state: &mut State, old_staked: Uint256, staked: Uint256) {
+    match (old_staked.is_zero(), staked.is_zero()) {
+        (true, false) => {
+            state.staker_count += 1;
+        }
+        (false, true) => {
+            state.staker_count -= 1;
+        }
+        _ => {}
+    }
+}
+
+/// Stake weighted by the position's lock multiplier, used for every reward calculation
+fn get_weight(staking: &StakerInfo) -> StdResult<Uint256> {
+    mul_div_floor(staking.staked, staking.multiplier, PERCENTS)
+}
+
+/// Swaps a position's previous weight for its current one in `State.total_weighted_staked`
+fn update_total_weight(state: &mut State, old_weight: Uint256, weight: Uint256) -> StdResult<()> {
+    state.total_weighted_staked = state.total_weighted_staked
+        .checked_sub(old_weight)?
+        .checked_add(weight)?;
+
+    Ok(())
+}
+
+/// Lock end and the weight `staking` holds on top of its stake until then
+fn lock_boost(staking: &StakerInfo) -> StdResult<Option<(u64, Uint256)>> {
+    if staking.multiplier == PERCENTS {
+        return Ok(None);
+    }
+
+    let boost = get_weight(staking)?.checked_sub(staking.staked)?;
+
+    Ok(Some((staking.lock_end.seconds(), boost)))
+}
+
+/// Moves a position's boost in `LOCK_EXPIRIES` from the lock it had to the one it holds now
+fn update_lock_expiry(
+    storage: &mut dyn Storage,
+    old_boost: Option<(u64, Uint256)>,
+    boost: Option<(u64, Uint256)>
+) -> StdResult<()> {
+    if old_boost == boost {
+        return Ok(());
+    }
+
+    if let Some((lock_end, old_boost)) = old_boost {
+        let mut expiry = LOCK_EXPIRIES.load(storage, lock_end)?;
+        expiry.boost = expiry.boost.checked_sub(old_boost)?;
+        expiry.positions -= 1;
+
+        if expiry.positions == 0 {
+            LOCK_EXPIRIES.remove(storage, lock_end);
+        } else {
+            LOCK_EXPIRIES.save(storage, lock_end, &expiry)?;
+        }
+    }
+
+    if let Some((lock_end, boost)) = boost {
+        let mut expiry = LOCK_EXPIRIES.may_load(storage, lock_end)?.unwrap_or_default();
+        expiry.boost = expiry.boost.checked_add(boost)?;
+        expiry.positions += 1;
+
+        LOCK_EXPIRIES.save(storage, lock_end, &expiry)?;
+    }
+
+    Ok(())
+}
+
+/// `update_lock_expiry` for the pending locks a simulation projects with
+fn move_lock_boost(
+    pending: &mut BTreeMap<u64, Uint256>,
+    old_boost: Option<(u64, Uint256)>,
+    boost: Option<(u64, Uint256)>
+) -> StdResult<()> {
+    if let Some((lock_end, old_boost)) = old_boost {
+        if let Some(total) = pending.get_mut(&lock_end) {
+            *total = total.checked_sub(old_boost)?;
+        }
+    }
+
+    if let Some((lock_end, boost)) = boost {
+        let total = pending.entry(lock_end).or_default();
+        *total = total.checked_add(boost)?;
+    }
+
+    Ok(())
+}
+
+fn send_tokens_msg(denom: &Denom, recipient: &Addr, amount: Uint256) -> StdResult<CosmosMsg> {
+    let amount = Uint128::try_from(amount)?;
+
+    let msg = match denom {
+        Denom::Native(denom) =>
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: recipient.to_string(),
+                amount: vec![Coin {
+                    amount,
+                    denom: denom.to_string(),
+                }],
+            }),
+        Denom::Cw20(contract_addr) =>
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: contract_addr.to_string(),
+                msg: to_json_binary(
+                    &(Cw20ExecuteMsg::Transfer {
+                        recipient: recipient.to_string(),
+                        amount,
+                    })
+                )?,
+                funds: vec![],
+            }),
+    };
+
+    Ok(msg)
+}
+
+fn query_token_balance(deps: Deps, denom: &Denom, address: &Addr) -> StdResult<Uint256> {
+    let balance = match denom {
+        Denom::Native(denom) => {
+            deps.querier.query_balance(address.to_string(), denom.to_string())?.amount
+        }
+        Denom::Cw20(contract_addr) => {
+            let res: BalanceResponse = deps.querier.query_wasm_smart(
+                contract_addr.to_string(),
+                &(Cw20QueryMsg::Balance { address: address.to_string() })
+            )?;
+
+            res.balance
+        }
+    };
+
+    Ok(Uint256::from_uint128(balance))
+}
+
+fn ensure_reserve_covers_liability(stream: &RewardStream) -> Result<(), ContractError> {
+    if stream.reward_reserve < stream.total_liability {
+        return Err(ContractError::InsufficientContractBalance {
+            denom: denom_label(&stream.denom),
+            reserve: stream.reward_reserve,
+            liability: stream.total_liability,
+        });
+    }
+
+    Ok(())
+}
+
+fn denom_label(denom: &Denom) -> String {
+    match denom {
+        Denom::Native(denom) => denom.to_string(),
+        Denom::Cw20(contract_addr) => contract_addr.to_string(),
+    }
+}
+
+/// Moves every reward index forward to `block_time` and records the locks that ended on the way
+fn update_ci(
+    storage: &mut dyn Storage,
+    config: &Config,
+    state: &mut State,
+    block_time: Timestamp
+) -> StdResult<ExpiredLocks> {
+    let expired = project_ci(storage, config, state, block_time)?;
+
+    save_expired_locks(storage, &expired)?;
+
+    Ok(expired)
+}
+
+/// `update_ci` without writing anything, for queries and settlements that may be discarded
+fn project_ci(
+    storage: &dyn Storage,
+    config: &Config,
+    state: &mut State,
+    block_time: Timestamp
+) -> StdResult<ExpiredLocks> {
+    let pending = pending_lock_expiries(storage, state, block_time)?;
+
+    advance_ci(config, state, block_time, &pending)
+}
+
+/// Boost of every lock that ends after the last index update and up to `block_time`
+fn pending_lock_expiries(
+    storage: &dyn Storage,
+    state: &State,
+    block_time: Timestamp
+) -> StdResult<BTreeMap<u64, Uint256>> {
+    let start = Bound::exclusive(state.ci_time_current.seconds());
+    let end = Bound::inclusive(block_time.seconds());
+
+    LOCK_EXPIRIES.range(storage, Some(start), Some(end), Order::Ascending)
+        .map(|item| item.map(|(lock_end, expiry)| (lock_end, expiry.boost)))
+        .collect()
+}
+
+fn save_expired_locks(storage: &mut dyn Storage, expired: &ExpiredLocks) -> StdResult<()> {
+    for (lock_end, cis) in expired {
+        let mut expiry = LOCK_EXPIRIES.load(storage, *lock_end)?;
+        expiry.cis = Some(cis.clone());
+
+        LOCK_EXPIRIES.save(storage, *lock_end, &expiry)?;
+    }
+
+    Ok(())
+}
+
+/// Moves every reward index forward to `block_time`, the boost of each lock in `pending` leaves
+/// `State.total_weighted_staked` at its end, returns the indices at each of those ends
+fn advance_ci(
+    config: &Config,
+    state: &mut State,
+    block_time: Timestamp,
+    pending: &BTreeMap<u64, Uint256>
+) -> StdResult<ExpiredLocks> {
+    let mut expired = ExpiredLocks::new();
+
+    let due = pending
+        .range(state.ci_time_current.seconds() + 1..)
+        .take_while(|(lock_end, _)| **lock_end <= block_time.seconds());
+
+    for (&lock_end, boost) in due {
+        accrue_ci(config, state, Timestamp::from_seconds(lock_end))?;
+
+        state.total_weighted_staked = state.total_weighted_staked.checked_sub(*boost)?;
+
+        expired.insert(
+            lock_end,
+            state.reward_streams
+                .iter()
+                .map(|stream| stream.ci_current)
+                .collect()
+        );
+    }
+
+    accrue_ci(config, state, block_time)?;
+
+    Ok(expired)
+}
+
+/// Moves every reward index forward to `block_time` at the current total weight
+fn accrue_ci(config: &Config, state: &mut State, block_time: Timestamp) -> StdResult<()> {
+    let new_cis = state.reward_streams
+        .iter()
+        .map(|stream| get_new_ci(config, state, stream, block_time))
+        .collect::<StdResult<Vec<Uint256>>>()?;
+
+    for (stream, new_ci) in state.reward_streams.iter_mut().zip(new_cis) {
+        // everything the pool earned over the interval is owed to stakers until claimed
+        stream.total_liability = settle_reward(
+            config,
+            state.total_weighted_staked,
+            stream.total_liability,
+            new_ci,
+            stream.ci_current
+        )?;
+        stream.ci_current = new_ci;
+
+        // `get_new_ci` already accrued each scheduled rate over its part of the interval
+        let due = stream.apr_schedule.partition_point(
+            |change| change.effective_time <= block_time
+        );
+
+        if let Some(change) = stream.apr_schedule.drain(..due).next_back() {
+            stream.apr = change.apr;
+        }
+    }
+
+    state.ci_time_current = block_time;
+
+    Ok(())
+}
+
+/// Settles the rewards `staking` earned since its last interaction on every stream, a lock that
+/// ended since then earns its boost up to `lock_end` and is released after it
+fn update_rewards(
+    storage: &dyn Storage,
+    config: &Config,
+    staking: &mut StakerInfo,
+    state: &State,
+    expired: &ExpiredLocks
+) -> StdResult<()> {
+    // streams added after the last interaction started accruing from the initial index
+    staking.rewards.resize(state.reward_streams.len(), StakerReward {
+        ci_0: ONE,
+        reward: Uint256::zero(),
+    });
+
+    if staking.multiplier != PERCENTS && staking.lock_end <= state.ci_time_current {
+        let lock_end = staking.lock_end.seconds();
+
+        let lock_cis = match expired.get(&lock_end) {
+            Some(cis) => cis.clone(),
+            None =>
+                LOCK_EXPIRIES.load(storage, lock_end)?.cis.ok_or_else(|| {
+                    StdError::generic_err(format!("Lock ending at {} has not expired", lock_end))
+                })?,
+        };
+
+        settle_rewards(config, staking, &lock_cis)?;
+        staking.multiplier = PERCENTS;
+    }
+
+    let cis = state.reward_streams
+        .iter()
+        .map(|stream| stream.ci_current)
+        .collect::<Vec<Uint256>>();
+
+    settle_rewards(config, staking, &cis)
+}
+
+/// Settles `staking` at its current weight up to `cis`, streams added after `cis` were taken are
+/// left for the next settlement
+fn settle_rewards(config: &Config, staking: &mut StakerInfo, cis: &[Uint256]) -> StdResult<()> {
+    let weight = get_weight(staking)?;
+
+    for (ci, staker_reward) in cis.iter().zip(staking.rewards.iter_mut()) {
+        staker_reward.reward = settle_reward(
+            config,
+            weight,
+            staker_reward.reward,
+            *ci,
+            staker_reward.ci_0
+        )?;
+        staker_reward.ci_0 = *ci;
+    }
+
+    Ok(())
+}
+
+/// Unclaimed `reward` after the index moved from `ci_0` to `ci`, compounding streams also grow
+/// the reward itself so the payout does not depend on how often anyone interacts
+fn settle_reward(
+    config: &Config,
+    weight: Uint256,
+    reward: Uint256,
+    ci: Uint256,
+    ci_0: Uint256
+) -> StdResult<Uint256> {
+    if compounds(config) {
+        return compounded_reward(weight, reward, ci, ci_0);
+    }
+
+    Ok(reward.checked_add(accrued_reward(weight, ci, ci_0)?)?)
+}
+
+fn compounds(config: &Config) -> bool {
+    config.reward_mode == RewardMode::Apr && config.accrual_mode == AccrualMode::Compound
+}
+
+fn get_new_ci(
+    config: &Config,
+    state: &State,
+    stream: &RewardStream,
+    block_time: Timestamp
+) -> StdResult<Uint256> {
+    if !stream.active || state.total_staked == Uint256::zero() {
+        return Ok(stream.ci_current);
+    }
+
+    // only the part of the interval inside the reward program accrues
+    let clamp = |time: Timestamp| {
+        let time = config.rewards_start.map_or(time, |start| time.max(start));
+        config.rewards_end.map_or(time, |end| time.min(end))
+    };
+
+    let mut period_start = clamp(state.ci_time_current);
+
+    if config.reward_mode == RewardMode::Emission {
+        let emission_end = stream.emission_end.map_or(block_time, |end| end.min(block_time));
+        let period_end = clamp(emission_end).max(period_start);
+
+        return emission_index(
+            stream.ci_current,
+            stream.emission_rate,
+            state.total_weighted_staked,
+            period_end.seconds() - period_start.seconds()
+        );
+    }
+
+    let apr_index = if compounds(config) { compound_index } else { simple_index };
+
+    let mut new_ci = stream.ci_current;
+    let mut apr = stream.apr;
+
+    // split the interval at every scheduled change so each part accrues at its own rate
+    let due_changes = stream.apr_schedule
+        .iter()
+        .take_while(|change| change.effective_time <= block_time);
+
+    for change in due_changes {
+        let period_end = clamp(change.effective_time).max(period_start);
+
+        new_ci = apr_index(new_ci, apr, period_end.seconds() - period_start.seconds())?;
+        apr = change.apr;
+        period_start = period_end;
+    }
+
+    let period_end = clamp(block_time).max(period_start);
+
+    apr_index(new_ci, apr, period_end.seconds() - period_start.seconds())
+}
+
+pub fn query_state(deps: Deps) -> StdResult<State> {
+    let state = STATE.load(deps.storage)?;
+
+    Ok(state)
+}
+
+pub fn query_total_staked(deps: Deps) -> StdResult<Uint256> {
+    let state = STATE.load(deps.storage)?;
+
+    Ok(state.total_staked)
+}
+
+pub fn query_total_withdrawn(deps: Deps) -> StdResult<Vec<RewardAmount>> {
+    let state = STATE.load(deps.storage)?;
+
+    let total_withdrawn = state.reward_streams
+        .into_iter()
+        .map(|stream| RewardAmount {
+            denom: stream.denom,
+            amount: stream.total_withdrawn,
+        })
+        .collect();
+
+    Ok(total_withdrawn)
+}
+
+pub fn query_config(deps: Deps) -> StdResult<Config> {
+    let config = CONFIG.load(deps.storage)?;
+
+    Ok(config)
+}
+
+pub fn query_reward_mode(deps: Deps) -> StdResult<RewardMode> {
+    let config = CONFIG.load(deps.storage)?;
+
+    Ok(config.reward_mode)
+}
+
+/// How much more can be staked, `None` where no cap applies
+pub fn query_remaining_capacity(
+    deps: Deps,
+    user: Option<Addr>
+) -> StdResult<RemainingCapacityResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let state = STATE.load(deps.storage)?;
+
+    let total = config.max_total_staked.map(|limit| limit.saturating_sub(state.total_staked));
+
+    let user = match (user, config.max_stake_per_user) {
+        (Some(user), Some(limit)) => {
+            let staked = USER_STAKINGS.may_load(deps.storage, user)?.map_or(
+                Uint256::zero(),
+                |staking| staking.staked
+            );
+            let remaining = limit.saturating_sub(staked);
+
+            // the pool cap can leave less room than the user cap
+            Some(total.map_or(remaining, |total| total.min(remaining)))
+        }
+        _ => total,
+    };
+
+    Ok(RemainingCapacityResponse { total, user })
+}
+
+pub fn query_stake_denom(deps: Deps) -> StdResult<Denom> {
+    let config = CONFIG.load(deps.storage)?;
+
+    Ok(config.stake_denom)
+}
+
+pub fn query_reward_streams(deps: Deps) -> StdResult<Vec<RewardStream>> {
+    let state = STATE.load(deps.storage)?;
+
+    Ok(state.reward_streams)
+}
+
+pub fn query_unbonding_period(deps: Deps) -> StdResult<u64> {
+    let config = CONFIG.load(deps.storage)?;
+
+    Ok(config.unbonding_period)
+}
+
+pub fn query_lock_tiers(deps: Deps) -> StdResult<Vec<LockTier>> {
+    let config = CONFIG.load(deps.storage)?;
+
+    Ok(config.lock_tiers)
+}
+
+/// Pending APR changes of the stream, changes that are already due are left out
+pub fn query_apr_schedule(
+    deps: Deps,
+    env: Env,
+    denom: UncheckedDenom
+) -> StdResult<Vec<AprChange>> {
+    let denom = denom.into_checked(deps)?;
+    let state = STATE.load(deps.storage)?;
+
+    let stream = state.reward_streams
+        .into_iter()
+        .find(|stream| stream.denom == denom)
+        .ok_or_else(|| StdError::generic_err("Reward stream not found"))?;
+
+    let schedule = stream.apr_schedule
+        .into_iter()
+        .filter(|change| change.effective_time > env.block.time)
+        .collect();
+
+    Ok(schedule)
+}
+
+/// Addresses that never staked get a zeroed position with one entry per reward stream
+pub fn query_staker_indo(deps: Deps, user: Addr) -> StdResult<StakerInfo> {
+    if let Some(info) = USER_STAKINGS.may_load(deps.storage, user)? {
+        return Ok(info);
+    }
+
+    let state = STATE.load(deps.storage)?;
+    let mut info = new_staker_info(Timestamp::from_seconds(0));
+
+    info.rewards = state.reward_streams
+        .iter()
+        .map(|stream| StakerReward {
+            ci_0: stream.ci_current,
+            reward: Uint256::zero(),
+        })
+        .collect();
+
+    Ok(info)
+}
+
+pub fn query_claimable_tokens(deps: Deps, env: Env, user: Addr) -> StdResult<Vec<RewardAmount>> {
+    // nothing staked accrues nothing, so unknown addresses get zero for every stream
+    let mut info = USER_STAKINGS.may_load(deps.storage, user)?.unwrap_or_else(|| {
+        new_staker_info(Timestamp::from_seconds(0))
+    });
+    let config = CONFIG.load(deps.storage)?;
+    let mut state = STATE.load(deps.storage)?;
+
+    let expired = project_ci(deps.storage, &config, &mut state, env.block.time)?;
+    update_rewards(deps.storage, &config, &mut info, &state, &expired)?;
+
+    Ok(reward_amounts(&state, &info))
+}
+
+pub fn query_list_stakers(
+    deps: Deps,
+    env: Env,
+    start_after: Option<Addr>,
+    limit: Option<u32>
+) -> StdResult<Vec<StakerSummary>> {
+    let config = CONFIG.load(deps.storage)?;
+    let mut state = STATE.load(deps.storage)?;
+
+    let expired = project_ci(deps.storage, &config, &mut state, env.block.time)?;
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    USER_STAKINGS.range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (address, info) = item?;
+
+            let rewards = reward_amounts(&state, &info);
+
+            let mut current = info.clone();
+            update_rewards(deps.storage, &config, &mut current, &state, &expired)?;
+
+            Ok(StakerSummary {
+                address,
+                staked: info.staked,
+                rewards,
+                claimable: reward_amounts(&state, &current),
+            })
+        })
+        .collect()
+}
+
+pub fn query_staker_count(deps: Deps) -> StdResult<u64> {
+    let state = STATE.load(deps.storage)?;
+
+    Ok(state.staker_count)
+}
+
+/// Stake of `user` at the start of block `height`, changes made in that block are not included
+pub fn query_staked_balance_at_height(
+    deps: Deps,
+    env: Env,
+    user: Addr,
+    height: Option<u64>
+) -> StdResult<StakedBalanceAtHeightResponse> {
+    let height = height.unwrap_or(env.block.height);
+    let balance = STAKED_BALANCES.may_load_at_height(deps.storage, user, height)?;
+
+    Ok(StakedBalanceAtHeightResponse {
+        balance: balance.unwrap_or_default(),
+        height,
+    })
+}
+
+/// Total stake at the start of block `height`
+pub fn query_total_staked_at_height(
+    deps: Deps,
+    env: Env,
+    height: Option<u64>
+) -> StdResult<TotalStakedAtHeightResponse> {
+    let height = height.unwrap_or(env.block.height);
+    let total = STAKED_TOTAL.may_load_at_height(deps.storage, height)?;
+
+    Ok(TotalStakedAtHeightResponse {
+        total: total.unwrap_or_default(),
+        height,
+    })
+}
+
+/// `StakedBalanceAtHeight` in the shape a DAO DAO proposal module queries
+pub fn query_voting_power_at_height(
+    deps: Deps,
+    env: Env,
+    address: String,
+    height: Option<u64>
+) -> StdResult<VotingPowerAtHeightResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let staked = query_staked_balance_at_height(deps, env, address, height)?;
+
+    Ok(VotingPowerAtHeightResponse {
+        power: Uint128::try_from(staked.balance)?,
+        height: staked.height,
+    })
+}
+
+pub fn query_total_power_at_height(
+    deps: Deps,
+    env: Env,
+    height: Option<u64>
+) -> StdResult<TotalPowerAtHeightResponse> {
+    let staked = query_total_staked_at_height(deps, env, height)?;
+
+    Ok(TotalPowerAtHeightResponse {
+        power: Uint128::try_from(staked.total)?,
+        height: staked.height,
+    })
+}
+
+pub fn query_dao(deps: Deps) -> StdResult<Addr> {
+    let dao = DAO.load(deps.storage)?;
+
+    Ok(dao)
+}
+
+pub fn query_info(deps: Deps) -> StdResult<InfoResponse> {
+    let info = get_contract_version(deps.storage)?;
+
+    Ok(InfoResponse { info })
+}
+
+/// Runs `Stake` against an in-memory copy of the position, nothing is written
+pub fn query_simulate_stake(
+    deps: Deps,
+    env: Env,
+    user: Addr,
+    amount: Uint256,
+    lock_duration: Option<u64>
+) -> Result<SimulationResponse, ContractError> {
+    ensure_not_paused(deps)?;
+
+    let config = CONFIG.load(deps.storage)?;
+
+    ensure_stake_started(&config, env.block.time)?;
+
+    let mut state = STATE.load(deps.storage)?;
+
+    let expired = project_ci(deps.storage, &config, &mut state, env.block.time)?;
+
+    let mut staking = USER_STAKINGS.may_load(deps.storage, user)?.unwrap_or_else(|| {
+        new_staker_info(env.block.time)
+    });
+
+    let old_boost = lock_boost(&staking)?;
+
+    update_rewards(deps.storage, &config, &mut staking, &state, &expired)?;
+
+    let old_weight = get_weight(&staking)?;
+
+    let compounded = auto_compound(deps, &config, &mut state, &mut staking)?;
+    apply_lock(&config, &mut staking, amount, lock_duration, env.block.time)?;
+
+    state.total_staked = state.total_staked.checked_add(amount)?;
+    update_total_weight(&mut state, old_weight, get_weight(&staking)?)?;
+
+    validate_stake_caps(&config, &state, &staking, compounded)?;
+
+    // the projection drops the simulated boost when the simulated lock ends
+    let mut pending = pending_lock_expiries(
+        deps.storage,
+        &state,
+        env.block.time.plus_seconds(SECONDS_IN_YEAR)
+    )?;
+    move_lock_boost(&mut pending, old_boost, lock_boost(&staking)?)?;
+
+    let response = simulation_response(
+        deps.storage,
+        &config,
+        &state,
+        &staking,
+        env.block.time,
+        None,
+        &pending
+    )?;
+
+    Ok(response)
+}
+
+/// Runs `Unstake` against an in-memory copy of the position, nothing is written
+pub fn query_simulate_unstake(
+    deps: Deps,
+    env: Env,
+    user: Addr,
+    amount: Uint256
+) -> Result<SimulationResponse, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    let mut staking = USER_STAKINGS.may_load(deps.storage, user)?.unwrap_or_else(|| {
+        new_staker_info(env.block.time)
+    });
+
+    ensure_can_unstake(&staking, amount, env.block.time)?;
+
+    let mut state = STATE.load(deps.storage)?;
+
+    let expired = project_ci(deps.storage, &config, &mut state, env.block.time)?;
+
+    update_rewards(deps.storage, &config, &mut staking, &state, &expired)?;
+
+    let old_weight = get_weight(&staking)?;
+
+    auto_compound(deps, &config, &mut state, &mut staking)?;
+
+    staking.staked = staking.staked.checked_sub(amount)?;
+    state.total_staked = state.total_staked.checked_sub(amount)?;
+    update_total_weight(&mut state, old_weight, get_weight(&staking)?)?;
+
+    validate_remaining_stake(&config, &staking)?;
+
+    let release_time = env.block.time.plus_seconds(config.unbonding_period);
+    let pending = pending_lock_expiries(
+        deps.storage,
+        &state,
+        env.block.time.plus_seconds(SECONDS_IN_YEAR)
+    )?;
+    let response = simulation_response(
+        deps.storage,
+        &config,
+        &state,
+        &staking,
+        env.block.time,
+        Some(release_time),
+        &pending
+    )?;
+
+    Ok(response)
+}
+
+pub fn query_project_rewards(
+    deps: Deps,
+    env: Env,
+    user: Addr,
+    at_time: Timestamp
+) -> StdResult<Vec<RewardAmount>> {
+    let config = CONFIG.load(deps.storage)?;
+    let state = STATE.load(deps.storage)?;
+    let staking = USER_STAKINGS.may_load(deps.storage, user)?.unwrap_or_else(|| {
+        new_staker_info(env.block.time)
+    });
+
+    // the past cannot be projected, earlier times return what is claimable now
+    let time = at_time.max(env.block.time);
+    let pending = pending_lock_expiries(deps.storage, &state, time)?;
+
+    project_rewards(deps.storage, &config, &state, &staking, time, &pending)
+}
+
+pub fn query_project_rewards_series(
+    deps: Deps,
+    env: Env,
+    user: Addr,
+    end_time: Timestamp,
+    points: u32
+) -> StdResult<Vec<ProjectedRewards>> {
+    let config = CONFIG.load(deps.storage)?;
+    let state = STATE.load(deps.storage)?;
+    let staking = USER_STAKINGS.may_load(deps.storage, user)?.unwrap_or_else(|| {
+        new_staker_info(env.block.time)
+    });
+
+    let points = points.min(MAX_PROJECTION_POINTS);
+    let span = end_time.seconds().saturating_sub(env.block.time.seconds());
+    let pending = pending_lock_expiries(deps.storage, &state, end_time)?;
+
+    // every point is projected from the stored state, like a single `ProjectRewards`
+    (1..=points)
+        .map(|point| {
+            let offset = (span * u64::from(point)) / u64::from(points);
+            let time = env.block.time.plus_seconds(offset);
+
+            Ok(ProjectedRewards {
+                time,
+                rewards: project_rewards(deps.storage, &config, &state, &staking, time, &pending)?,
+            })
+        })
+        .collect()
+}
+
+pub fn query_pending_withdrawals(deps: Deps, user: Addr) -> StdResult<Vec<PendingWithdrawal>> {
+    let withdrawals = PENDING_WITHDRAWALS.may_load(deps.storage, user)?.unwrap_or_default();
+
+    Ok(withdrawals)
+}
+
+pub fn query_pause_info(deps: Deps) -> StdResult<PauseInfo> {
+    let pause = PAUSE.load(deps.storage)?;
+
+    Ok(pause)
+}
+
+pub fn query_admin(deps: Deps) -> StdResult<Option<Addr>> {
+    let admin = ADMIN.may_load(deps.storage)?;
+
+    Ok(admin)
+}
+
+pub fn query_pending_admin(deps: Deps) -> StdResult<Option<PendingAdmin>> {
+    let pending_admin = PENDING_ADMIN.may_load(deps.storage)?;
+
+    Ok(pending_admin)
+}
+
+/// Addresses holding `role`, the owner is the single holder of `Role::Owner`
+pub fn query_role_holders(deps: Deps, role: Role) -> StdResult<Vec<Addr>> {
+    if role == Role::Owner {
+        return Ok(ADMIN.may_load(deps.storage)?.into_iter().collect());
+    }
+
+    ROLES.prefix(role.as_str()).keys(deps.storage, None, None, Order::Ascending).collect()
+}
+
+pub fn query_hooks(deps: Deps) -> StdResult<Vec<Addr>> {
+    HOOKS.keys(deps.storage, None, None, Order::Ascending).collect()
+}
+
+pub fn query_solvency(deps: Deps, env: Env) -> StdResult<Vec<SolvencyResponse>> {
+    let config = CONFIG.load(deps.storage)?;
+    let mut state = STATE.load(deps.storage)?;
+
+    project_ci(deps.storage, &config, &mut state, env.block.time)?;
+
+    state.reward_streams
+        .iter()
+        .map(|stream| {
+            let balance = query_token_balance(deps, &stream.denom, &env.contract.address)?;
+            let principal = if stream.denom == config.stake_denom {
+                state.total_staked.checked_add(state.total_unbonding)?
+            } else {
+                Uint256::zero()
+            };
+
+            Ok(SolvencyResponse {
+                denom: stream.denom.clone(),
+                balance,
+                principal,
+                reward_reserve: stream.reward_reserve,
+                liability: stream.total_liability,
+                surplus: stream.reward_reserve.saturating_sub(stream.total_liability),
+                deficit: stream.total_liability.saturating_sub(stream.reward_reserve),
+            })
+        })
+        .collect()
+}
+
+pub fn query_funded_rewards(deps: Deps, user: Addr) -> StdResult<Vec<RewardAmount>> {
+    let funded = REWARD_FUNDERS.may_load(deps.storage, user)?.unwrap_or_default();
+
+    Ok(funded)
 }
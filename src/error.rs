@@ -1,25 +1,149 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{ OverflowError, StdError, Uint256 };
+use cw_utils::ParseReplyError;
 use thiserror::Error;
 
+use crate::state::Role;
+
 #[derive(Error, Debug)]
 pub enum ContractError {
     #[error("{0}")] Std(#[from] StdError),
 
-    #[error("Unauthorized")] Unauthorized {},
+    #[error("{0}")] Overflow(#[from] OverflowError),
+
+    #[error("{0}")] ParseReply(#[from] ParseReplyError),
+
+    #[error("Unauthorized sender {sender}")] Unauthorized {
+        sender: String,
+    },
 
     #[error("Invalid funds were provided")] InvalidFunds {},
 
-    #[error("Invalid coin passed in funds")] InvalidCoin {},
+    #[error("Invalid coin {denom} passed in funds")] InvalidCoin {
+        denom: String,
+    },
 
     #[error("No tokens were staked")] CannotUnstake {},
 
-    #[error("Insufficient balance")] CannotUnstakeAmount {},
+    #[error("No staking position")] NoPosition {},
+
+    #[error("Insufficient balance: {staked} staked, {amount} requested")] CannotUnstakeAmount {
+        amount: Uint256,
+        staked: Uint256,
+    },
 
     #[error("No claims")] CannotClaim {},
 
-    #[error("Insufficient contract balance")] InsufficientContractBalance {},
+    #[error("No reward stream pays out the staking asset")] CompoundUnavailable {},
+
+    #[error("No unbonded tokens to withdraw")] NothingToWithdraw {},
+
+    #[error(
+        "Cannot change the stake denom while {amount} is staked or unbonding"
+    )] StakeDenomInUse {
+        amount: Uint256,
+    },
+
+    #[error(
+        "Insufficient contract balance: {denom} reserve {reserve} is below liability {liability}"
+    )] InsufficientContractBalance {
+        denom: String,
+        reserve: Uint256,
+        liability: Uint256,
+    },
+
+    #[error("Amount exceeds rewards not owed to stakers")] ExcessRewardsExceeded {},
 
     #[error("Invalid APR")] InvalidApr {},
 
-    #[error("Only admin")] OnlyAdmin {},
+    #[error("Invalid emission rate")] InvalidEmissionRate {},
+
+    #[error("Not supported in the current reward mode")] InvalidRewardMode {},
+
+    #[error("Invalid reward program window")] InvalidRewardsWindow {},
+
+    #[error("Reward program has not started")] RewardsNotStarted {},
+
+    #[error("Effective time must be in the future")] InvalidEffectiveTime {},
+
+    #[error("APR change already scheduled at that time")] AprChangeExists {},
+
+    #[error("APR change not found")] AprChangeNotFound {},
+
+    #[error("Reward stream already exists")] RewardStreamExists {},
+
+    #[error("Reward stream {denom} not found")] RewardStreamNotFound {
+        denom: String,
+    },
+
+    #[error("Missing role {role} for {address}")] MissingRole {
+        role: Role,
+        address: String,
+    },
+
+    #[error("Owner can only be transferred through ProposeAdmin")] OwnerNotGrantable {},
+
+    #[error("Only pending admin")] OnlyPendingAdmin {},
+
+    #[error("No pending admin proposal")] NoPendingAdmin {},
+
+    #[error("Admin proposal expired")] AdminProposalExpired {},
+
+    #[error("Contract is paused")] Paused {},
+
+    #[error("Invalid lock tier")] InvalidLockTier {},
+
+    #[error("Invalid staking caps")] InvalidStakingCaps {},
+
+    #[error("Total stake {amount} exceeds cap {limit}")] TotalStakeCapExceeded {
+        amount: Uint256,
+        limit: Uint256,
+    },
+
+    #[error("Stake {amount} is below minimum {limit}")] StakeBelowMinimum {
+        amount: Uint256,
+        limit: Uint256,
+    },
+
+    #[error("Stake {amount} exceeds per-user cap {limit}")] UserStakeCapExceeded {
+        amount: Uint256,
+        limit: Uint256,
+    },
+
+    #[error("Cannot migrate from contract {contract}")] InvalidMigrationContract {
+        contract: String,
+    },
+
+    #[error("Invalid contract version {version}")] InvalidVersion {
+        version: String,
+    },
+
+    #[error("Cannot downgrade from {stored} to {current}")] CannotDowngrade {
+        stored: String,
+        current: String,
+    },
+
+    #[error("Stake is locked until {until}")] Locked {
+        until: u64,
+    },
+
+    #[error("Hook {address} is already registered")] HookAlreadyRegistered {
+        address: String,
+    },
+
+    #[error("Hook {address} is not registered")] HookNotRegistered {
+        address: String,
+    },
+
+    #[error("Unknown reply id {id}")] UnknownReplyId {
+        id: u64,
+    },
+
+    #[error("Receipt token code does not report transfers")] InvalidReceiptToken {},
+
+    #[error("Unstake by sending receipt tokens")] ReceiptTokenRequired {},
+
+    #[error("Send all {staked} receipt tokens to withdraw, got {amount}")] ReceiptAmountMismatch {
+        amount: Uint256,
+        staked: Uint256,
+    },
 }
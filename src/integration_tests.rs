@@ -1,25 +1,421 @@
 #[cfg(test)]
 pub mod tests {
     use crate::{ helpers::CwTemplateContract, ContractError };
-    use crate::msg::InstantiateMsg;
+    use crate::migrations::v0_1_0;
+    use crate::msg::{
+        ExecuteMsg,
+        InfoResponse,
+        InstantiateMsg,
+        MigrateMsg,
+        QueryMsg,
+        ReceiveMsg,
+        RewardAmount,
+        RewardStreamMsg,
+        StakeChangedHookMsg,
+        StakedBalanceAtHeightResponse,
+        TotalPowerAtHeightResponse,
+        VotingPowerAtHeightResponse,
+    };
+    use crate::state::{
+        AccrualMode,
+        LockTier,
+        RewardMode,
+        RewardStream,
+        StakerInfo,
+        StakerReward,
+        State,
+        PERCENTS,
+    };
     use anyhow::Error;
 
-    use cosmwasm_std::{ Addr, Coin, Empty, Uint128, Uint256 };
+    use cosmwasm_schema::cw_serde;
+    use cosmwasm_std::{
+        to_json_binary,
+        Addr,
+        Binary,
+        BlockInfo,
+        Coin,
+        Decimal256,
+        Deps,
+        DepsMut,
+        Empty,
+        Env,
+        MessageInfo,
+        Reply,
+        Response,
+        StdError,
+        StdResult,
+        SubMsg,
+        Timestamp,
+        Uint128,
+        Uint256,
+        WasmMsg,
+    };
+    use cw2::{ set_contract_version, ContractVersion };
+    use cw20::{
+        BalanceResponse,
+        Cw20Coin,
+        Cw20ExecuteMsg,
+        Cw20QueryMsg,
+        Cw20ReceiveMsg,
+        MinterResponse,
+        Denom,
+        TokenInfoResponse,
+        UncheckedDenom,
+    };
     use cw_multi_test::{ App, AppBuilder, AppResponse, Contract, ContractWrapper, Executor };
+    use cw_storage_plus::{ Item, Map };
+    use cw_utils::parse_reply_instantiate_data;
 
     pub fn contract_template() -> Box<dyn Contract<Empty>> {
         let contract = ContractWrapper::new(
             crate::contract::execute,
             crate::contract::instantiate,
             crate::contract::query
+        )
+            .with_migrate(crate::contract::migrate)
+            .with_reply(crate::contract::reply);
+        Box::new(contract)
+    }
+
+    /// Stand-in for a deployed v0.1.0 contract, writes the legacy storage layout at instantiate
+    pub fn legacy_contract_template() -> Box<dyn Contract<Empty>> {
+        let contract = ContractWrapper::new(legacy_execute, legacy_instantiate, legacy_query);
+        Box::new(contract)
+    }
+
+    #[cw_serde]
+    pub struct LegacyInstantiateMsg {
+        pub contract_name: String,
+        pub contract_version: String,
+        pub apr: Uint256,
+        pub injex_token: String,
+        pub admin: String,
+        pub total_withdrawn: Uint256,
+        pub ci_current: Uint256,
+        pub stakers: Vec<(String, v0_1_0::StakerInfo)>,
+    }
+
+    fn legacy_instantiate(
+        deps: DepsMut,
+        env: Env,
+        _info: MessageInfo,
+        msg: LegacyInstantiateMsg
+    ) -> StdResult<Response> {
+        let mut total_staked = Uint256::zero();
+
+        for (address, info) in msg.stakers {
+            let address = deps.api.addr_validate(&address)?;
+
+            total_staked += info.staked;
+            v0_1_0::USER_STAKINGS.save(deps.storage, address, &info)?;
+        }
+
+        set_contract_version(deps.storage, msg.contract_name, msg.contract_version)?;
+        v0_1_0::CONFIG.save(deps.storage, &(v0_1_0::Config {
+            apr: msg.apr,
+            injex_token: msg.injex_token,
+        }))?;
+        v0_1_0::STATE.save(deps.storage, &(v0_1_0::State {
+            total_staked,
+            total_withdrawn: msg.total_withdrawn,
+            ci_current: msg.ci_current,
+            ci_time_current: env.block.time,
+        }))?;
+        crate::state::ADMIN.save(deps.storage, &deps.api.addr_validate(&msg.admin)?)?;
+
+        Ok(Response::new())
+    }
+
+    fn legacy_execute(
+        _deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        _msg: Empty
+    ) -> StdResult<Response> {
+        Err(StdError::generic_err("unsupported by legacy contract"))
+    }
+
+    fn legacy_query(_deps: Deps, _env: Env, _msg: Empty) -> StdResult<Binary> {
+        Err(StdError::generic_err("unsupported by legacy contract"))
+    }
+
+    /// Minimal CW20 implementation used to exercise the `Receive` hook and CW20 payouts
+    pub fn cw20_contract_template() -> Box<dyn Contract<Empty>> {
+        let contract = ContractWrapper::new(cw20_execute, cw20_instantiate, cw20_query);
+        Box::new(contract)
+    }
+
+    /// Lenient about the optional parts, so it also instantiates from a receipt token message
+    #[cw_serde]
+    pub struct MockCw20InstantiateMsg {
+        pub name: String,
+        pub symbol: String,
+        pub decimals: u8,
+        #[serde(default)]
+        pub initial_balances: Vec<Cw20Coin>,
+        #[serde(default)]
+        pub mint: Option<MinterResponse>,
+    }
+
+    const CW20_TOKEN_INFO: Item<TokenInfoResponse> = Item::new("token_info");
+    const CW20_MINTER: Item<Option<MinterResponse>> = Item::new("minter");
+    const CW20_BALANCES: Map<&Addr, Uint128> = Map::new("balance");
+
+    fn cw20_instantiate(
+        deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        msg: MockCw20InstantiateMsg
+    ) -> StdResult<Response> {
+        let mut total_supply = Uint128::zero();
+
+        for coin in msg.initial_balances {
+            let address = deps.api.addr_validate(&coin.address)?;
+
+            CW20_BALANCES.save(deps.storage, &address, &coin.amount)?;
+            total_supply += coin.amount;
+        }
+
+        CW20_TOKEN_INFO.save(deps.storage, &TokenInfoResponse {
+            name: msg.name,
+            symbol: msg.symbol,
+            decimals: msg.decimals,
+            total_supply,
+        })?;
+        CW20_MINTER.save(deps.storage, &msg.mint)?;
+
+        Ok(Response::new())
+    }
+
+    fn cw20_move(deps: DepsMut, from: &Addr, to: &Addr, amount: Uint128) -> StdResult<()> {
+        CW20_BALANCES.update(deps.storage, from, |balance| -> StdResult<Uint128> {
+            Ok(balance.unwrap_or_default().checked_sub(amount)?)
+        })?;
+        CW20_BALANCES.update(deps.storage, to, |balance| -> StdResult<Uint128> {
+            Ok(balance.unwrap_or_default() + amount)
+        })?;
+
+        Ok(())
+    }
+
+    fn cw20_execute(
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        msg: Cw20ExecuteMsg
+    ) -> StdResult<Response> {
+        match msg {
+            Cw20ExecuteMsg::Transfer { recipient, amount } => {
+                let recipient = deps.api.addr_validate(&recipient)?;
+                cw20_move(deps, &info.sender, &recipient, amount)?;
+
+                Ok(Response::new())
+            }
+            Cw20ExecuteMsg::Send { contract, amount, msg } => {
+                let contract = deps.api.addr_validate(&contract)?;
+                cw20_move(deps, &info.sender, &contract, amount)?;
+
+                let receive_msg = (Cw20ReceiveMsg {
+                    sender: info.sender.to_string(),
+                    amount,
+                    msg,
+                }).into_cosmos_msg(contract)?;
+
+                Ok(Response::new().add_message(receive_msg))
+            }
+            _ => Err(StdError::generic_err("unsupported by mock cw20")),
+        }
+    }
+
+    fn cw20_query(deps: Deps, _env: Env, msg: Cw20QueryMsg) -> StdResult<Binary> {
+        match msg {
+            Cw20QueryMsg::Balance { address } => {
+                let address = deps.api.addr_validate(&address)?;
+                let balance = CW20_BALANCES.may_load(deps.storage, &address)?.unwrap_or_default();
+
+                to_json_binary(&(BalanceResponse { balance }))
+            }
+            Cw20QueryMsg::TokenInfo {} => to_json_binary(&CW20_TOKEN_INFO.load(deps.storage)?),
+            Cw20QueryMsg::Minter {} => to_json_binary(&CW20_MINTER.load(deps.storage)?),
+            _ => Err(StdError::generic_err("unsupported by mock cw20")),
+        }
+    }
+
+    pub fn receipt_token_template() -> Box<dyn Contract<Empty>> {
+        let contract = ContractWrapper::new(
+            injex_receipt_token::contract::execute,
+            injex_receipt_token::contract::instantiate,
+            injex_receipt_token::contract::query
+        );
+        Box::new(contract)
+    }
+
+    /// Hook receiver that records every `StakeChangedHook`, or rejects them when `fail` is set
+    pub fn hook_receiver_template() -> Box<dyn Contract<Empty>> {
+        let contract = ContractWrapper::new(
+            hook_receiver_execute,
+            hook_receiver_instantiate,
+            hook_receiver_query
         );
         Box::new(contract)
     }
 
+    #[cw_serde]
+    pub struct MockHookReceiverInstantiateMsg {
+        pub fail: bool,
+    }
+
+    const HOOK_RECEIVER_FAIL: Item<bool> = Item::new("fail");
+    const HOOK_RECEIVER_RECEIVED: Item<Vec<StakeChangedHookMsg>> = Item::new("received");
+
+    fn hook_receiver_instantiate(
+        deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        msg: MockHookReceiverInstantiateMsg
+    ) -> StdResult<Response> {
+        HOOK_RECEIVER_FAIL.save(deps.storage, &msg.fail)?;
+        HOOK_RECEIVER_RECEIVED.save(deps.storage, &vec![])?;
+
+        Ok(Response::new())
+    }
+
+    fn hook_receiver_execute(
+        deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        msg: StakeChangedHookMsg
+    ) -> StdResult<Response> {
+        if HOOK_RECEIVER_FAIL.load(deps.storage)? {
+            return Err(StdError::generic_err("hook receiver failed"));
+        }
+
+        HOOK_RECEIVER_RECEIVED.update(deps.storage, |mut received| -> StdResult<_> {
+            received.push(msg);
+
+            Ok(received)
+        })?;
+
+        Ok(Response::new())
+    }
+
+    fn hook_receiver_query(deps: Deps, _env: Env, _msg: Empty) -> StdResult<Binary> {
+        to_json_binary(&HOOK_RECEIVER_RECEIVED.load(deps.storage)?)
+    }
+
+    /// Minimal DAO core, instantiates the staking contract as its voting module and forwards
+    /// voting power queries to it the way proposal modules reach it through the core
+    pub fn dao_contract_template() -> Box<dyn Contract<Empty>> {
+        let contract = ContractWrapper::new(dao_execute, dao_instantiate, dao_query).with_reply(
+            dao_reply
+        );
+        Box::new(contract)
+    }
+
+    #[cw_serde]
+    pub struct MockDaoInstantiateMsg {
+        pub voting_module_code_id: u64,
+        pub voting_module_msg: Binary,
+    }
+
+    #[cw_serde]
+    pub enum MockDaoQueryMsg {
+        VotingModule {},
+        VotingPowerAtHeight {
+            address: String,
+            height: Option<u64>,
+        },
+        TotalPowerAtHeight {
+            height: Option<u64>,
+        },
+    }
+
+    const DAO_VOTING_MODULE: Item<Addr> = Item::new("voting_module");
+
+    fn dao_instantiate(
+        _deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        msg: MockDaoInstantiateMsg
+    ) -> StdResult<Response> {
+        let instantiate_msg = WasmMsg::Instantiate {
+            admin: None,
+            code_id: msg.voting_module_code_id,
+            msg: msg.voting_module_msg,
+            funds: vec![],
+            label: "voting module".to_string(),
+        };
+
+        Ok(Response::new().add_submessage(SubMsg::reply_on_success(instantiate_msg, 0)))
+    }
+
+    fn dao_reply(deps: DepsMut, _env: Env, msg: Reply) -> StdResult<Response> {
+        let res = parse_reply_instantiate_data(msg).map_err(|err| {
+            StdError::generic_err(err.to_string())
+        })?;
+
+        DAO_VOTING_MODULE.save(deps.storage, &deps.api.addr_validate(&res.contract_address)?)?;
+
+        Ok(Response::new())
+    }
+
+    fn dao_execute(
+        _deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        _msg: Empty
+    ) -> StdResult<Response> {
+        Err(StdError::generic_err("unsupported by mock dao"))
+    }
+
+    fn dao_query(deps: Deps, _env: Env, msg: MockDaoQueryMsg) -> StdResult<Binary> {
+        let voting_module = DAO_VOTING_MODULE.load(deps.storage)?;
+
+        match msg {
+            MockDaoQueryMsg::VotingModule {} => to_json_binary(&voting_module),
+            MockDaoQueryMsg::VotingPowerAtHeight { address, height } => {
+                let res: VotingPowerAtHeightResponse = deps.querier.query_wasm_smart(
+                    voting_module,
+                    &(QueryMsg::VotingPowerAtHeight { address, height })
+                )?;
+
+                to_json_binary(&res)
+            }
+            MockDaoQueryMsg::TotalPowerAtHeight { height } => {
+                let res: TotalPowerAtHeightResponse = deps.querier.query_wasm_smart(
+                    voting_module,
+                    &(QueryMsg::TotalPowerAtHeight { height })
+                )?;
+
+                to_json_binary(&res)
+            }
+        }
+    }
+
     pub const ADMIN: &str = "inj1mvnlejy385wy498z7hvmflrsdfludx8ztxsx7m";
     pub const USER: &str = "inj1mvnlejy385wy498z7hvmflrsdfludx8ztxsx7m";
     pub const INJEX_TOKEN: &str = "INJX";
     pub const USDT: &str = "USDT";
+    pub const UNBONDING_PERIOD: u64 = 604_800; // 7 days
+    pub const DAY: u64 = 86_400;
+
+    pub fn lock_tiers() -> Vec<LockTier> {
+        vec![
+            LockTier {
+                duration: 30 * DAY,
+                multiplier: Uint256::from_u128(12_500_u128), // 1.25x
+            },
+            LockTier {
+                duration: 90 * DAY,
+                multiplier: Uint256::from_u128(15_000_u128), // 1.5x
+            },
+            LockTier {
+                duration: 180 * DAY,
+                multiplier: Uint256::from_u128(20_000_u128), // 2x
+            }
+        ]
+    }
 
     pub fn mock_app() -> App {
         AppBuilder::new().build(|router, _, storage| {
@@ -47,43 +443,149 @@ pub mod tests {
     }
 
     pub fn proper_instantiate(should_add_balance_to_contract: bool) -> (App, CwTemplateContract) {
+        proper_instantiate_with_reward(INJEX_TOKEN, should_add_balance_to_contract)
+    }
+
+    pub fn proper_instantiate_with_reward(
+        reward_denom: &str,
+        should_add_balance_to_contract: bool
+    ) -> (App, CwTemplateContract) {
+        proper_instantiate_with_msg(
+            instantiate_msg(reward_denom),
+            reward_denom,
+            should_add_balance_to_contract
+        )
+    }
+
+    /// Native INJX staking with a single reward stream at 20% APR and no reward program window
+    pub fn instantiate_msg(reward_denom: &str) -> InstantiateMsg {
+        InstantiateMsg {
+            reward_mode: RewardMode::Apr,
+            accrual_mode: AccrualMode::Simple,
+            stake_denom: UncheckedDenom::Native(INJEX_TOKEN.to_string()),
+            reward_streams: vec![RewardStreamMsg {
+                denom: UncheckedDenom::Native(reward_denom.to_string()),
+                apr: Decimal256::percent(20),
+                emission_rate: Uint256::zero(),
+            }],
+            admin: Addr::unchecked(ADMIN).to_string(),
+            unbonding_period: UNBONDING_PERIOD,
+            lock_tiers: lock_tiers(),
+            rewards_start: None,
+            rewards_end: None,
+            stake_requires_start: false,
+            max_total_staked: None,
+            min_stake_amount: None,
+            max_stake_per_user: None,
+            dao: None,
+            receipt_token: None,
+        }
+    }
+
+    pub fn proper_instantiate_with_msg(
+        msg: InstantiateMsg,
+        reward_denom: &str,
+        should_add_balance_to_contract: bool
+    ) -> (App, CwTemplateContract) {
         let mut app: App = mock_app();
         let cw_template_id = app.store_code(contract_template());
 
-        let msg = InstantiateMsg {
-            apr: Uint256::from_u128(2_000_u128), // 20%
-            injex_token: INJEX_TOKEN.to_string(),
-            admin: Addr::unchecked(ADMIN).to_string(),
+        let cw_template_contract_addr = app
+            .instantiate_contract(cw_template_id, Addr::unchecked(ADMIN), &msg, &[], "test", None)
+            .unwrap();
+
+        if should_add_balance_to_contract {
+            app.execute_contract(
+                Addr::unchecked(USER),
+                cw_template_contract_addr.clone(),
+                &ExecuteMsg::FundRewards {},
+                &[
+                    Coin {
+                        denom: reward_denom.to_string(),
+                        amount: Uint128::new(1000000000000000000000),
+                    },
+                ]
+            ).unwrap();
+        }
+
+        let cw_template_contract = CwTemplateContract(cw_template_contract_addr);
+
+        (app, cw_template_contract)
+    }
+
+    pub fn proper_instantiate_cw20(
+        should_add_balance_to_contract: bool
+    ) -> (App, CwTemplateContract, Addr) {
+        let mut app: App = mock_app();
+        let cw_template_id = app.store_code(contract_template());
+        let cw20_id = app.store_code(cw20_contract_template());
+
+        let cw20_msg = MockCw20InstantiateMsg {
+            name: "Injex".to_string(),
+            symbol: INJEX_TOKEN.to_string(),
+            decimals: 18,
+            initial_balances: vec![Cw20Coin {
+                address: USER.to_string(),
+                amount: Uint128::new(100000000000000000000000000),
+            }],
+            mint: None,
         };
 
-        let send_funds: &[Coin] = if should_add_balance_to_contract {
-            &[
-                Coin {
-                    denom: INJEX_TOKEN.to_string(),
-                    amount: Uint128::new(1000000000000000000000),
-                },
-            ]
-        } else {
-            &[]
+        let cw20_addr = app
+            .instantiate_contract(cw20_id, Addr::unchecked(ADMIN), &cw20_msg, &[], "injx", None)
+            .unwrap();
+
+        let msg = InstantiateMsg {
+            reward_mode: RewardMode::Apr,
+            accrual_mode: AccrualMode::Simple,
+            stake_denom: UncheckedDenom::Cw20(cw20_addr.to_string()),
+            reward_streams: vec![RewardStreamMsg {
+                denom: UncheckedDenom::Cw20(cw20_addr.to_string()),
+                apr: Decimal256::percent(20),
+                emission_rate: Uint256::zero(),
+            }],
+            admin: Addr::unchecked(ADMIN).to_string(),
+            unbonding_period: UNBONDING_PERIOD,
+            lock_tiers: lock_tiers(),
+            rewards_start: None,
+            rewards_end: None,
+            stake_requires_start: false,
+            max_total_staked: None,
+            min_stake_amount: None,
+            max_stake_per_user: None,
+            dao: None,
+            receipt_token: None,
         };
 
         let cw_template_contract_addr = app
-            .instantiate_contract(
-                cw_template_id,
-                Addr::unchecked(ADMIN),
-                &msg,
-                send_funds,
-                "test",
-                None
-            )
+            .instantiate_contract(cw_template_id, Addr::unchecked(ADMIN), &msg, &[], "test", None)
             .unwrap();
 
+        if should_add_balance_to_contract {
+            let fund_msg = Cw20ExecuteMsg::Send {
+                contract: cw_template_contract_addr.to_string(),
+                amount: Uint128::new(1000000000000000000000),
+                msg: to_json_binary(&ReceiveMsg::FundRewards {}).unwrap(),
+            };
+
+            app.execute_contract(Addr::unchecked(USER), cw20_addr.clone(), &fund_msg, &[]).unwrap();
+        }
+
         let cw_template_contract = CwTemplateContract(cw_template_contract_addr);
 
-        (app, cw_template_contract)
+        (app, cw_template_contract, cw20_addr)
+    }
+
+    pub fn query_cw20_balance(app: &App, token: &Addr, address: &str) -> Uint128 {
+        let res: BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(token, &(Cw20QueryMsg::Balance { address: address.to_string() }))
+            .unwrap();
+
+        res.balance
     }
 
-    pub fn expect_error(res: Result<AppResponse, Error>, reason: String) -> () {
+    pub fn expect_error(res: Result<AppResponse, Error>, reason: String) {
         assert!(res.is_err());
 
         if let Err(err) = res {
@@ -95,4 +597,389 @@ pub mod tests {
             }
         }
     }
+
+    pub const LEGACY_STAKER: &str = "inj12vpajtjf5cvmk2w737m0t8qwwkyjz0xgvxwyus";
+
+    /// Deploys a populated v0.1.0 contract, returns it with the code id of the current contract
+    pub fn proper_instantiate_legacy(
+        contract_name: &str,
+        contract_version: &str
+    ) -> (App, Addr, u64) {
+        let mut app: App = mock_app();
+        let legacy_id = app.store_code(legacy_contract_template());
+        let cw_template_id = app.store_code(contract_template());
+
+        let msg = LegacyInstantiateMsg {
+            contract_name: contract_name.to_string(),
+            contract_version: contract_version.to_string(),
+            apr: Uint256::from_u128(2_000_u128), // 20%
+            injex_token: INJEX_TOKEN.to_string(),
+            admin: ADMIN.to_string(),
+            total_withdrawn: Uint256::from_u128(3_000000000000000000_u128),
+            ci_current: Uint256::from_u128(1_020000000000000000_u128),
+            stakers: vec![
+                (
+                    USER.to_string(),
+                    v0_1_0::StakerInfo {
+                        staked: Uint256::from_u128(100_000000000000000000_u128),
+                        block_time: app.block_info().time,
+                        ci_0: crate::math::ONE,
+                        reward: Uint256::from_u128(5_000000000000000000_u128),
+                    },
+                ),
+                (
+                    LEGACY_STAKER.to_string(),
+                    v0_1_0::StakerInfo {
+                        staked: Uint256::from_u128(50_000000000000000000_u128),
+                        block_time: app.block_info().time,
+                        ci_0: Uint256::from_u128(1_010000000000000000_u128),
+                        reward: Uint256::zero(),
+                    },
+                )
+            ],
+        };
+
+        let legacy_addr = app
+            .instantiate_contract(
+                legacy_id,
+                Addr::unchecked(ADMIN),
+                &msg,
+                &[
+                    Coin {
+                        denom: INJEX_TOKEN.to_string(),
+                        amount: Uint128::new(1150000000000000000000),
+                    },
+                ],
+                "legacy",
+                Some(ADMIN.to_string())
+            )
+            .unwrap();
+
+        (app, legacy_addr, cw_template_id)
+    }
+
+    #[test]
+    fn migrate_populated_v0_1_0() {
+        let (mut app, contract_addr, new_code_id) = proper_instantiate_legacy("injex-staking", "0.1.0");
+        let migrated_at = app.block_info().time;
+
+        app.migrate_contract(
+            Addr::unchecked(ADMIN),
+            contract_addr.clone(),
+            &(MigrateMsg {}),
+            new_code_id
+        ).unwrap();
+
+        let version = cw2::query_contract_info(&app.wrap(), contract_addr.clone()).unwrap();
+        assert_eq!(version.contract, "injex-staking");
+        assert_eq!(version.version, env!("CARGO_PKG_VERSION"));
+
+        let stake_denom: Denom = app
+            .wrap()
+            .query_wasm_smart(contract_addr.clone(), &(QueryMsg::GetStakeDenom {}))
+            .unwrap();
+        assert_eq!(stake_denom, Denom::Native(INJEX_TOKEN.to_string()));
+
+        let unbonding_period: u64 = app
+            .wrap()
+            .query_wasm_smart(contract_addr.clone(), &(QueryMsg::GetUnbondingPeriod {}))
+            .unwrap();
+        assert_eq!(unbonding_period, 0);
+
+        let state: State = app
+            .wrap()
+            .query_wasm_smart(contract_addr.clone(), &(QueryMsg::GetState {}))
+            .unwrap();
+        assert_eq!(state, State {
+            total_staked: Uint256::from_u128(150_000000000000000000_u128),
+            total_unbonding: Uint256::zero(),
+            total_weighted_staked: Uint256::from_u128(150_000000000000000000_u128),
+            staker_count: 2,
+            ci_time_current: migrated_at,
+            reward_streams: vec![RewardStream {
+                denom: Denom::Native(INJEX_TOKEN.to_string()),
+                apr: Decimal256::percent(20),
+                emission_rate: Uint256::zero(),
+                emission_end: None,
+                ci_current: Uint256::from_u128(1_020000000000000000_u128),
+                total_withdrawn: Uint256::from_u128(3_000000000000000000_u128),
+                // 7 owed to USER and 50 * (1.02 - 1.01) to LEGACY_STAKER
+                total_liability: Uint256::from_u128(7_500000000000000000_u128),
+                // 1150 held minus 150 staked
+                reward_reserve: Uint256::from_u128(1000_000000000000000000_u128),
+                apr_schedule: vec![],
+                active: true,
+            }],
+        });
+
+        let staker_info: StakerInfo = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &(QueryMsg::GetStakerInfo { user: Addr::unchecked(LEGACY_STAKER) })
+            )
+            .unwrap();
+        assert_eq!(staker_info, StakerInfo {
+            staked: Uint256::from_u128(50_000000000000000000_u128),
+            block_time: migrated_at,
+            multiplier: PERCENTS,
+            lock_end: Timestamp::from_seconds(0),
+            auto_compound: false,
+            rewards: vec![StakerReward {
+                ci_0: Uint256::from_u128(1_010000000000000000_u128),
+                reward: Uint256::zero(),
+            }],
+        });
+
+        // legacy stakes count as voting power from the block after the upgrade
+        let balance: StakedBalanceAtHeightResponse = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &(QueryMsg::StakedBalanceAtHeight {
+                    user: Addr::unchecked(LEGACY_STAKER),
+                    height: Some(app.block_info().height + 1),
+                })
+            )
+            .unwrap();
+        assert_eq!(balance.balance, Uint256::from_u128(50_000000000000000000_u128));
+
+        let dao: Addr = app
+            .wrap()
+            .query_wasm_smart(contract_addr.clone(), &(QueryMsg::Dao {}))
+            .unwrap();
+        assert_eq!(dao, Addr::unchecked(ADMIN));
+
+        // rewards accrued before the upgrade stay claimable: 5 stored + 100 * (1.02 - 1)
+        let claimable: Vec<RewardAmount> = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &(QueryMsg::GetClaimableAmount { user: Addr::unchecked(USER) })
+            )
+            .unwrap();
+        assert_eq!(claimable, vec![RewardAmount {
+            denom: Denom::Native(INJEX_TOKEN.to_string()),
+            amount: Uint256::from_u128(7_000000000000000000_u128),
+        }]);
+
+        let balance_before = app.wrap().query_balance(USER, INJEX_TOKEN).unwrap().amount;
+
+        app.execute_contract(
+            Addr::unchecked(USER),
+            contract_addr.clone(),
+            &(ExecuteMsg::Claim {}),
+            &[]
+        ).unwrap();
+        app.execute_contract(
+            Addr::unchecked(USER),
+            contract_addr.clone(),
+            &(ExecuteMsg::Unstake { amount: Uint256::from_u128(100_000000000000000000_u128) }),
+            &[]
+        ).unwrap();
+        app.execute_contract(
+            Addr::unchecked(USER),
+            contract_addr.clone(),
+            &(ExecuteMsg::WithdrawUnbonded {}),
+            &[]
+        ).unwrap();
+
+        let balance_after = app.wrap().query_balance(USER, INJEX_TOKEN).unwrap().amount;
+        assert_eq!(balance_after - balance_before, Uint128::new(107000000000000000000));
+    }
+
+    #[test]
+    fn migrate_twice_is_noop() {
+        let (mut app, contract_addr, new_code_id) = proper_instantiate_legacy("injex-staking", "0.1.0");
+
+        app.migrate_contract(
+            Addr::unchecked(ADMIN),
+            contract_addr.clone(),
+            &(MigrateMsg {}),
+            new_code_id
+        ).unwrap();
+
+        let state: State = app
+            .wrap()
+            .query_wasm_smart(contract_addr.clone(), &(QueryMsg::GetState {}))
+            .unwrap();
+
+        let res = app
+            .migrate_contract(
+                Addr::unchecked(ADMIN),
+                contract_addr.clone(),
+                &(MigrateMsg {}),
+                new_code_id
+            )
+            .unwrap();
+
+        let applied = res.events
+            .iter()
+            .flat_map(|event| event.attributes.iter())
+            .find(|attribute| attribute.key == "applied_migrations")
+            .unwrap();
+        assert_eq!(applied.value, "");
+
+        let state_after: State = app
+            .wrap()
+            .query_wasm_smart(contract_addr, &(QueryMsg::GetState {}))
+            .unwrap();
+        assert_eq!(state, state_after);
+    }
+
+    #[test]
+    fn migrate_refuses_downgrade() {
+        let (mut app, contract_addr, new_code_id) = proper_instantiate_legacy("injex-staking", "9.9.9");
+
+        let res = app.migrate_contract(
+            Addr::unchecked(ADMIN),
+            contract_addr,
+            &(MigrateMsg {}),
+            new_code_id
+        );
+
+        expect_error(
+            res,
+            format!("Cannot downgrade from 9.9.9 to {}", env!("CARGO_PKG_VERSION"))
+        );
+    }
+
+    #[test]
+    fn migrate_refuses_other_contract() {
+        let (mut app, contract_addr, new_code_id) = proper_instantiate_legacy(
+            "crates.io:cw20-base",
+            "0.1.0"
+        );
+
+        let res = app.migrate_contract(
+            Addr::unchecked(ADMIN),
+            contract_addr,
+            &(MigrateMsg {}),
+            new_code_id
+        );
+
+        expect_error(res, "Cannot migrate from contract crates.io:cw20-base".to_string());
+    }
+
+    #[test]
+    fn dao_voting_module() {
+        let mut app: App = mock_app();
+        let cw_template_id = app.store_code(contract_template());
+        let dao_id = app.store_code(dao_contract_template());
+
+        let dao_msg = MockDaoInstantiateMsg {
+            voting_module_code_id: cw_template_id,
+            voting_module_msg: to_json_binary(&instantiate_msg(INJEX_TOKEN)).unwrap(),
+        };
+
+        let dao_addr = app
+            .instantiate_contract(dao_id, Addr::unchecked(ADMIN), &dao_msg, &[], "dao", None)
+            .unwrap();
+        let voting_module: Addr = app
+            .wrap()
+            .query_wasm_smart(dao_addr.clone(), &(MockDaoQueryMsg::VotingModule {}))
+            .unwrap();
+
+        // without an explicit `dao` the instantiating core is the DAO
+        let dao: Addr = app
+            .wrap()
+            .query_wasm_smart(voting_module.clone(), &(QueryMsg::Dao {}))
+            .unwrap();
+        assert_eq!(dao, dao_addr);
+
+        let info: InfoResponse = app
+            .wrap()
+            .query_wasm_smart(voting_module.clone(), &(QueryMsg::Info {}))
+            .unwrap();
+        assert_eq!(info.info, ContractVersion {
+            contract: "injex-staking".to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+        });
+
+        let stake_msg = ExecuteMsg::Stake { lock_duration: None };
+        let funds = vec![Coin {
+            denom: INJEX_TOKEN.to_string(),
+            amount: Uint128::new(100_000000000000000000),
+        }];
+        let next_block = |app: &mut App| {
+            let block_info = app.block_info();
+
+            app.set_block(BlockInfo {
+                chain_id: block_info.chain_id,
+                height: block_info.height + 1,
+                time: block_info.time.plus_seconds(5),
+            });
+        };
+
+        app.execute_contract(Addr::unchecked(USER), voting_module.clone(), &stake_msg, &funds)
+            .unwrap();
+
+        next_block(&mut app);
+
+        // a proposal opening here reads voting power at this height
+        let proposal_height = app.block_info().height;
+
+        app.execute_contract(Addr::unchecked(USER), voting_module.clone(), &stake_msg, &funds)
+            .unwrap();
+
+        next_block(&mut app);
+
+        let power: VotingPowerAtHeightResponse = app
+            .wrap()
+            .query_wasm_smart(
+                dao_addr.clone(),
+                &(MockDaoQueryMsg::VotingPowerAtHeight {
+                    address: USER.to_string(),
+                    height: Some(proposal_height),
+                })
+            )
+            .unwrap();
+        assert_eq!(power, VotingPowerAtHeightResponse {
+            power: Uint128::new(100_000000000000000000),
+            height: proposal_height,
+        });
+
+        let total: TotalPowerAtHeightResponse = app
+            .wrap()
+            .query_wasm_smart(
+                dao_addr.clone(),
+                &(MockDaoQueryMsg::TotalPowerAtHeight { height: Some(proposal_height) })
+            )
+            .unwrap();
+        assert_eq!(total.power, Uint128::new(100_000000000000000000));
+
+        let power: VotingPowerAtHeightResponse = app
+            .wrap()
+            .query_wasm_smart(
+                dao_addr.clone(),
+                &(MockDaoQueryMsg::VotingPowerAtHeight { address: USER.to_string(), height: None })
+            )
+            .unwrap();
+        assert_eq!(power, VotingPowerAtHeightResponse {
+            power: Uint128::new(200_000000000000000000),
+            height: app.block_info().height,
+        });
+
+        let res: StdResult<VotingPowerAtHeightResponse> = app
+            .wrap()
+            .query_wasm_smart(
+                voting_module,
+                &(QueryMsg::VotingPowerAtHeight { address: "".to_string(), height: None })
+            );
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn dao_set_at_instantiate() {
+        let dao = Addr::unchecked(LEGACY_STAKER);
+        let msg = InstantiateMsg {
+            dao: Some(dao.to_string()),
+            ..instantiate_msg(INJEX_TOKEN)
+        };
+
+        let (app, contract) = proper_instantiate_with_msg(msg, INJEX_TOKEN, false);
+
+        let res: Addr = app.wrap().query_wasm_smart(contract.addr(), &(QueryMsg::Dao {})).unwrap();
+        assert_eq!(res, dao);
+    }
 }
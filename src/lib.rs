@@ -1,5 +1,7 @@
 pub mod contract;
 mod error;
+pub mod math;
+pub mod migrations;
 pub mod msg;
 pub mod state;
 mod test;
@@ -0,0 +1,96 @@
+use cosmwasm_std::{
+    Decimal256,
+    DivideByZeroError,
+    OverflowError,
+    OverflowOperation,
+    StdError,
+    StdResult,
+    Uint256,
+    Uint512,
+};
+
+/// Fixed-point scale of reward indices, an index of `ONE` is 1.0
+pub const ONE: Uint256 = Uint256::from_u128(1_000_000_000_000_000_000_u128);
+
+pub const SECONDS_IN_YEAR: u64 = 31_536_000;
+
+/// `a * b / denominator` rounded down, the product is kept in 512 bits so only a result that
+/// does not fit `Uint256` fails
+pub fn mul_div_floor(a: Uint256, b: Uint256, denominator: Uint256) -> StdResult<Uint256> {
+    if denominator.is_zero() {
+        return Err(StdError::divide_by_zero(DivideByZeroError::new(a.full_mul(b))));
+    }
+
+    Uint256::try_from(a.full_mul(b) / Uint512::from(denominator)).map_err(|_| {
+        StdError::overflow(OverflowError::new(OverflowOperation::Mul, a, b))
+    })
+}
+
+/// Index after `seconds` of simple interest at `apr`, it grows linearly so a reward depends only
+/// on the weight and the time it was held, rounded down
+pub fn simple_index(index: Uint256, apr: Decimal256, seconds: u64) -> StdResult<Uint256> {
+    // `apr.atomics()` already carries the `ONE` scale, so this is the growth in index units
+    let growth = mul_div_floor(apr.atomics(), Uint256::from(seconds), SECONDS_IN_YEAR.into())?;
+
+    Ok(index.checked_add(growth)?)
+}
+
+/// Index after `seconds` of per-second compounding at `apr`, `index * (1 + apr / year)^seconds`,
+/// the per-second rate and every step of the exponentiation round down
+pub fn compound_index(index: Uint256, apr: Decimal256, seconds: u64) -> StdResult<Uint256> {
+    let rate = Decimal256::new(apr.atomics() / Uint256::from(SECONDS_IN_YEAR));
+    let growth = checked_pow(Decimal256::one().checked_add(rate)?, seconds)?;
+
+    mul_div_floor(index, growth.atomics(), ONE)
+}
+
+/// Reward-per-share index after `rate` tokens a second were split by weight for `seconds`,
+/// rounded down so the sum of every staker's share never exceeds what was emitted
+pub fn emission_index(
+    index: Uint256,
+    rate: Uint256,
+    total_weighted_staked: Uint256,
+    seconds: u64
+) -> StdResult<Uint256> {
+    let elapsed = Uint256::from(seconds).checked_mul(ONE)?;
+    let emitted = mul_div_floor(rate, elapsed, total_weighted_staked)?;
+
+    Ok(index.checked_add(emitted)?)
+}
+
+/// Reward of `weight` while the index moved from `index_0` to `index`, rounded down
+pub fn accrued_reward(weight: Uint256, index: Uint256, index_0: Uint256) -> StdResult<Uint256> {
+    mul_div_floor(weight, index.checked_sub(index_0)?, ONE)
+}
+
+/// Unclaimed `reward` after `weight` and the reward itself grew from `index_0` to `index` on a
+/// compounding index, rounded down
+pub fn compounded_reward(
+    weight: Uint256,
+    reward: Uint256,
+    index: Uint256,
+    index_0: Uint256
+) -> StdResult<Uint256> {
+    let balance = mul_div_floor(weight.checked_add(reward)?, index, index_0)?;
+
+    Ok(balance.checked_sub(weight)?)
+}
+
+/// `base^exp` by squaring, so a long idle period costs at most 2 * 64 multiplications
+fn checked_pow(mut base: Decimal256, mut exp: u64) -> StdResult<Decimal256> {
+    let mut result = Decimal256::one();
+
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = result.checked_mul(base)?;
+        }
+
+        exp >>= 1;
+
+        if exp > 0 {
+            base = base.checked_mul(base)?;
+        }
+    }
+
+    Ok(result)
+}
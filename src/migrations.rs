@@ -0,0 +1,194 @@
+use cosmwasm_std::{
+    Addr,
+    Decimal256,
+    DepsMut,
+    Env,
+    Order,
+    StdError,
+    StdResult,
+    Timestamp,
+    Uint256,
+};
+use cw20::Denom;
+use semver::Version;
+
+use crate::math::accrued_reward;
+use crate::state::{
+    AccrualMode,
+    Config,
+    PauseInfo,
+    RewardMode,
+    RewardStream,
+    StakerInfo,
+    StakerReward,
+    State,
+    ADMIN,
+    CONFIG,
+    DAO,
+    PAUSE,
+    PERCENTS,
+    STAKED_BALANCES,
+    STAKED_TOTAL,
+    STATE,
+    USER_STAKINGS,
+};
+
+type MigrationStep = fn(DepsMut, &Env) -> StdResult<()>;
+
+/// Storage upgrades in release order, a step runs when the stored version is below its version
+const MIGRATIONS: &[(&str, MigrationStep)] = &[("0.2.0", migrate_to_v0_2_0)];
+
+/// Runs every storage upgrade between `from` and `to`, returns the versions that were applied
+pub fn run_migrations(
+    mut deps: DepsMut,
+    env: &Env,
+    from: &Version,
+    to: &Version
+) -> StdResult<Vec<String>> {
+    let mut applied = vec![];
+
+    for (version, step) in MIGRATIONS {
+        let target = Version::parse(version).map_err(|err| StdError::generic_err(err.to_string()))?;
+
+        if *from < target && target <= *to {
+            step(deps.branch(), env)?;
+            applied.push(version.to_string());
+        }
+    }
+
+    Ok(applied)
+}
+
+/// Storage layout of the v0.1.0 release, a single native token staked and paid out at one APR
+pub mod v0_1_0 {
+    use schemars::JsonSchema;
+    use serde::{ Deserialize, Serialize };
+
+    use cosmwasm_std::{ Addr, Timestamp, Uint256 };
+    use cw_storage_plus::{ Item, Map };
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    pub struct Config {
+        /// basis points, 10000 is 100%
+        pub apr: Uint256,
+        pub injex_token: String,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    pub struct State {
+        pub total_staked: Uint256,
+        pub total_withdrawn: Uint256,
+        pub ci_current: Uint256,
+        pub ci_time_current: Timestamp,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    pub struct StakerInfo {
+        pub staked: Uint256,
+        pub block_time: Timestamp,
+        pub ci_0: Uint256,
+        pub reward: Uint256,
+    }
+
+    pub const CONFIG: Item<Config> = Item::new("config");
+
+    pub const STATE: Item<State> = Item::new("state");
+
+    pub const USER_STAKINGS: Map<Addr, StakerInfo> = Map::new("user_stakings");
+}
+
+/// Splits the single token into `stake_denom` and one reward stream, adds unbonding and lock fields
+fn migrate_to_v0_2_0(deps: DepsMut, env: &Env) -> StdResult<()> {
+    let old_config = v0_1_0::CONFIG.load(deps.storage)?;
+    let old_state = v0_1_0::STATE.load(deps.storage)?;
+
+    // v0.1.0 held principal and rewards in one balance, everything above the stake is reserve
+    let balance = deps.querier.query_balance(&env.contract.address, &old_config.injex_token)?;
+    let reward_reserve = Uint256::from_uint128(balance.amount).saturating_sub(
+        old_state.total_staked
+    );
+
+    let denom = Denom::Native(old_config.injex_token);
+
+    // v0.1.0 paid out on unstake right away, so no unbonding period and no lock tiers
+    CONFIG.save(deps.storage, &(Config {
+        reward_mode: RewardMode::Apr,
+        accrual_mode: AccrualMode::Simple,
+        stake_denom: denom.clone(),
+        unbonding_period: 0,
+        lock_tiers: vec![],
+        rewards_start: None,
+        rewards_end: None,
+        stake_requires_start: false,
+        max_total_staked: None,
+        min_stake_amount: None,
+        max_stake_per_user: None,
+        receipt_token: None,
+    }))?;
+
+    let stakers = v0_1_0::USER_STAKINGS
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<(Addr, v0_1_0::StakerInfo)>>>()?;
+
+    // v0.1.0 did not track what it owed, so sum every staker's accrued rewards
+    let total_liability = stakers
+        .iter()
+        .try_fold(Uint256::zero(), |total, (_, info)| -> StdResult<Uint256> {
+            let accrued = accrued_reward(info.staked, old_state.ci_current, info.ci_0)?;
+
+            Ok(total.checked_add(info.reward)?.checked_add(accrued)?)
+        })?;
+
+    let staker_count = stakers
+        .iter()
+        .filter(|(_, info)| info.staked != Uint256::zero())
+        .count() as u64;
+
+    STATE.save(deps.storage, &(State {
+        total_staked: old_state.total_staked,
+        total_unbonding: Uint256::zero(),
+        // every legacy position is unlocked, so its weight equals its stake
+        total_weighted_staked: old_state.total_staked,
+        staker_count,
+        ci_time_current: old_state.ci_time_current,
+        reward_streams: vec![RewardStream {
+            denom,
+            apr: Decimal256::from_ratio(old_config.apr, PERCENTS),
+            emission_rate: Uint256::zero(),
+            emission_end: None,
+            ci_current: old_state.ci_current,
+            total_withdrawn: old_state.total_withdrawn,
+            total_liability,
+            reward_reserve,
+            apr_schedule: vec![],
+            active: true,
+        }],
+    }))?;
+
+    PAUSE.save(deps.storage, &PauseInfo::default())?;
+
+    // v0.1.0 was not a voting module, its admin stands in for the DAO
+    let admin = ADMIN.load(deps.storage)?;
+    DAO.save(deps.storage, &admin)?;
+
+    // voting power snapshots start at the upgrade, v0.1.0 kept no history
+    STAKED_TOTAL.save(deps.storage, &old_state.total_staked, env.block.height)?;
+
+    for (address, old_info) in stakers {
+        STAKED_BALANCES.save(deps.storage, address.clone(), &old_info.staked, env.block.height)?;
+
+        USER_STAKINGS.save(deps.storage, address, &(StakerInfo {
+            staked: old_info.staked,
+            block_time: old_info.block_time,
+            multiplier: PERCENTS,
+            lock_end: Timestamp::from_seconds(0),
+            auto_compound: false,
+            rewards: vec![StakerReward {
+                ci_0: old_info.ci_0,
+                reward: old_info.reward,
+            }],
+        }))?;
+    }
+
+    Ok(())
+}
@@ -1,19 +1,167 @@
-use cosmwasm_std::{ Addr, Uint256 };
+use cosmwasm_std::{ Addr, Decimal256, Timestamp, Uint128, Uint256 };
 use cosmwasm_schema::cw_serde;
+use cw2::ContractVersion;
+use cw20::{ Cw20ReceiveMsg, Denom, UncheckedDenom };
+use cw_utils::Expiration;
+
+use crate::state::{ AccrualMode, LockTier, RewardMode, Role };
 
 #[cw_serde]
 pub struct InstantiateMsg {
-    pub apr: Uint256,
-    pub injex_token: String,
+    pub reward_mode: RewardMode,
+    pub accrual_mode: AccrualMode,
+    pub stake_denom: UncheckedDenom,
+    pub reward_streams: Vec<RewardStreamMsg>,
     pub admin: String,
+    pub unbonding_period: u64,
+    pub lock_tiers: Vec<LockTier>,
+    pub rewards_start: Option<Timestamp>,
+    pub rewards_end: Option<Timestamp>,
+    pub stake_requires_start: bool,
+    pub max_total_staked: Option<Uint256>,
+    pub min_stake_amount: Option<Uint256>,
+    pub max_stake_per_user: Option<Uint256>,
+    /// DAO using the contract as its voting module, the instantiating account when `None` as
+    /// a DAO core instantiates its own voting module
+    pub dao: Option<String>,
+    /// instantiates a receipt token minted for every staked token
+    pub receipt_token: Option<ReceiptTokenMsg>,
+}
+
+/// CW20 the contract instantiates as its receipt token and holds the minter role of
+#[cw_serde]
+pub struct ReceiptTokenMsg {
+    /// code of `contracts/receipt-token`, which reports every transfer back so the staked
+    /// position moves with the tokens, instantiation fails for any other code
+    pub code_id: u64,
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+}
+
+#[cw_serde]
+pub struct RewardStreamMsg {
+    pub denom: UncheckedDenom,
+    /// yearly rate as a fraction, `0.2` is 20%, required in `RewardMode::Apr`
+    #[serde(default)]
+    pub apr: Decimal256,
+    /// tokens per second, required in `RewardMode::Emission`
+    #[serde(default)]
+    pub emission_rate: Uint256,
+}
+
+#[cw_serde]
+pub struct MigrateMsg {}
+
+/// Amount of a single reward asset
+#[cw_serde]
+pub struct RewardAmount {
+    pub denom: Denom,
+    pub amount: Uint256,
+}
+
+/// Reward stream reserve against what the contract owes in its denom
+#[cw_serde]
+pub struct SolvencyResponse {
+    pub denom: Denom,
+    pub balance: Uint256,
+    /// staked and unbonding tokens, non-zero only for the stream paying out the staked token
+    pub principal: Uint256,
+    pub reward_reserve: Uint256,
+    /// rewards accrued by every staker and not claimed yet
+    pub liability: Uint256,
+    pub surplus: Uint256,
+    pub deficit: Uint256,
+}
+
+/// Room left under the staking caps, `None` means unlimited
+#[cw_serde]
+pub struct RemainingCapacityResponse {
+    pub total: Option<Uint256>,
+    /// also bounded by `total`, equals `total` when no user was given
+    pub user: Option<Uint256>,
+}
+
+/// Page entry of `ListStakers`
+#[cw_serde]
+pub struct StakerSummary {
+    pub address: Addr,
+    pub staked: Uint256,
+    /// rewards stored at the staker's last interaction
+    pub rewards: Vec<RewardAmount>,
+    /// rewards claimable right now, including what accrued since the last interaction
+    pub claimable: Vec<RewardAmount>,
+}
+
+/// Position and pool after a simulated `Stake` or `Unstake`
+#[cw_serde]
+pub struct SimulationResponse {
+    pub staked: Uint256,
+    pub total_staked: Uint256,
+    /// rewards the position holds right after the action
+    pub rewards: Vec<RewardAmount>,
+    /// rewards the position would accrue over the next year if nothing else changed
+    pub projected_yearly_rewards: Vec<RewardAmount>,
+    /// when the unstaked tokens can be withdrawn, set for `SimulateUnstake` only
+    pub release_time: Option<Timestamp>,
+}
+
+/// Claimable rewards projected at `time`
+#[cw_serde]
+pub struct ProjectedRewards {
+    pub time: Timestamp,
+    pub rewards: Vec<RewardAmount>,
+}
+
+/// Stake of a user at the start of block `height`
+#[cw_serde]
+pub struct StakedBalanceAtHeightResponse {
+    pub balance: Uint256,
+    pub height: u64,
+}
+
+#[cw_serde]
+pub struct TotalStakedAtHeightResponse {
+    pub total: Uint256,
+    pub height: u64,
+}
+
+/// Voting power of an address, DAO DAO voting module interface
+#[cw_serde]
+pub struct VotingPowerAtHeightResponse {
+    pub power: Uint128,
+    pub height: u64,
+}
+
+/// Voting power of every staker, DAO DAO voting module interface
+#[cw_serde]
+pub struct TotalPowerAtHeightResponse {
+    pub power: Uint128,
+    pub height: u64,
+}
+
+/// Contract name and version, DAO DAO voting module interface
+#[cw_serde]
+pub struct InfoResponse {
+    pub info: ContractVersion,
 }
 
 #[cw_serde]
 pub enum QueryMsg {
     GetTotalStaked {},
     GetTotalWithdrawn {},
-    GetInjexToken {},
-    GetApr {},
+    GetStakeDenom {},
+    GetRewardStreams {},
+    GetUnbondingPeriod {},
+    GetLockTiers {},
+    GetConfig {},
+    GetRewardMode {},
+    GetRemainingCapacity {
+        user: Option<Addr>,
+    },
+    GetAprSchedule {
+        denom: UncheckedDenom,
+    },
     GetState {},
     GetStakerInfo {
         user: Addr,
@@ -21,22 +169,204 @@ pub enum QueryMsg {
     GetClaimableAmount {
         user: Addr,
     },
+    /// stakers ordered by address, `limit` defaults to 10 and is capped at 30
+    ListStakers {
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+    },
+    GetStakerCount {},
+    /// stake at the start of block `height`, the current block when `None`
+    StakedBalanceAtHeight {
+        user: Addr,
+        height: Option<u64>,
+    },
+    TotalStakedAtHeight {
+        height: Option<u64>,
+    },
+    /// stake at `height` as DAO DAO voting power
+    VotingPowerAtHeight {
+        address: String,
+        height: Option<u64>,
+    },
+    TotalPowerAtHeight {
+        height: Option<u64>,
+    },
+    Dao {},
+    Info {},
+    SimulateStake {
+        user: Addr,
+        amount: Uint256,
+        lock_duration: Option<u64>,
+    },
+    SimulateUnstake {
+        user: Addr,
+        amount: Uint256,
+    },
+    /// claimable rewards at `at_time` if the user does not interact until then
+    ProjectRewards {
+        user: Addr,
+        at_time: Timestamp,
+    },
+    /// `ProjectRewards` at `points` evenly spaced times up to `end_time`, at most 100 points
+    ProjectRewardsSeries {
+        user: Addr,
+        end_time: Timestamp,
+        points: u32,
+    },
+    GetPendingWithdrawals {
+        user: Addr,
+    },
+    GetPauseInfo {},
+    GetSolvency {},
+    GetFundedRewards {
+        user: Addr,
+    },
+    GetAdmin {},
+    GetPendingAdmin {},
+    GetRoleHolders {
+        role: Role,
+    },
+    ListHooks {},
 }
 
 #[cw_serde]
 pub enum ExecuteMsg {
-    Stake {},
+    /// `lock_duration` must match a configured lock tier, `None` keeps the current lock and adds
+    /// the tokens unboosted
+    Stake {
+        lock_duration: Option<u64>,
+    },
+    Receive(Cw20ReceiveMsg),
+    /// credits the attached coin to the reward reserve of the stream paying it out
+    FundRewards {},
+    /// withdraws reward reserve that is not owed to stakers
+    WithdrawExcessRewards {
+        denom: UncheckedDenom,
+        amount: Uint256,
+    },
     Claim {},
+    /// moves rewards paid in the staking asset into `staked`
+    Compound {},
+    SetAutoCompound {
+        enabled: bool,
+    },
+    /// unavailable with a receipt token, send the receipt tokens with `ReceiveMsg::Unstake`
     Unstake {
         amount: Uint256,
     },
+    WithdrawUnbonded {},
+    /// returns the whole principal and forfeits unpaid rewards, works while paused, a locked
+    /// position only while paused
+    EmergencyWithdraw {},
+    /// `ReceiptHookMsg` of the receipt token, moves `amount` of stake from `from` to `to`
+    ReceiptTransfer {
+        from: String,
+        to: String,
+        amount: Uint128,
+    },
+    AddRewardStream {
+        denom: UncheckedDenom,
+        #[serde(default)]
+        apr: Decimal256,
+        #[serde(default)]
+        emission_rate: Uint256,
+    },
+    RemoveRewardStream {
+        denom: UncheckedDenom,
+    },
     ChangeApr {
-        new_apr: Uint256,
+        denom: UncheckedDenom,
+        new_apr: Decimal256,
+    },
+    ChangeEmissionRate {
+        denom: UncheckedDenom,
+        emission_rate: Uint256,
     },
-    ChangeAdmin {
+    /// emits `amount` evenly over the next `duration` seconds
+    SetEmissionBudget {
+        denom: UncheckedDenom,
+        amount: Uint256,
+        duration: u64,
+    },
+    /// switches the stream to `apr` at `effective_time` without another transaction
+    ScheduleAprChange {
+        denom: UncheckedDenom,
+        effective_time: Timestamp,
+        apr: Decimal256,
+    },
+    CancelAprChange {
+        denom: UncheckedDenom,
+        effective_time: Timestamp,
+    },
+    /// nominates a new admin, who takes over once they send `AcceptAdmin`
+    ProposeAdmin {
+        address: String,
+        expiry: Option<Expiration>,
+    },
+    AcceptAdmin {},
+    CancelAdminProposal {},
+    RenounceAdmin {},
+    GrantRole {
+        role: Role,
+        address: String,
+    },
+    RevokeRole {
+        role: Role,
+        address: String,
+    },
+    /// registers a contract to receive `StakeChangedHookMsg` on every stake change
+    AddHook {
         address: String,
     },
-    ChangeInjexToken {
-        new_injex_token: String,
+    RemoveHook {
+        address: String,
+    },
+    /// moves `rewards_end` later, or ends a program that had no end at or after the current
+    /// block time
+    ExtendRewardsEnd {
+        rewards_end: Timestamp,
+    },
+    /// only while nothing is staked or unbonding, those tokens are owed in the old denom
+    ChangeStakeDenom {
+        new_stake_denom: UncheckedDenom,
+    },
+    ChangeUnbondingPeriod {
+        new_unbonding_period: u64,
+    },
+    UpdateLockTiers {
+        lock_tiers: Vec<LockTier>,
+    },
+    /// `None` removes a cap
+    UpdateStakingCaps {
+        max_total_staked: Option<Uint256>,
+        min_stake_amount: Option<Uint256>,
+        max_stake_per_user: Option<Uint256>,
+    },
+    Pause {
+        reason: Option<String>,
+    },
+    Unpause {},
+}
+
+/// Sent to every registered hook contract after a position's stake changes
+#[cw_serde]
+pub enum StakeChangedHookMsg {
+    StakeChangedHook {
+        addr: Addr,
+        old_staked: Uint256,
+        new_staked: Uint256,
+    },
+}
+
+/// Hook messages accepted inside `Cw20ReceiveMsg.msg`
+#[cw_serde]
+pub enum ReceiveMsg {
+    Stake {
+        lock_duration: Option<u64>,
     },
+    FundRewards {},
+    /// burns the receipt tokens sent and unstakes as many tokens from the sender's position
+    Unstake {},
+    /// burns the receipt tokens sent, which must cover the sender's whole stake
+    EmergencyWithdraw {},
 }
@@ -1,34 +1,192 @@
+use std::fmt;
+
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Addr, Timestamp, Uint256};
-use cw_storage_plus::{Item, Map};
+use cosmwasm_std::{Addr, Decimal256, Empty, Timestamp, Uint256};
+use cw20::Denom;
+use cw_storage_plus::{Item, Map, SnapshotItem, SnapshotMap, Strategy};
+use cw_utils::Expiration;
+
+use crate::msg::RewardAmount;
 
 // 100%
 pub const PERCENTS: Uint256 = Uint256::from_u128(10_000_u128);
 
+/// How reward streams accrue
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RewardMode {
+    /// every staked token earns the stream's APR, total payout grows with TVL
+    Apr,
+    /// the stream pays a fixed amount per second split by weight among stakers
+    Emission,
+}
+
+/// How an APR stream's index grows between interactions
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AccrualMode {
+    /// interest on the staked principal only
+    Simple,
+    /// per-second compounding, unclaimed rewards earn the rate as well
+    Compound,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
-    pub apr: Uint256,
-    pub injex_token: String,
+    pub reward_mode: RewardMode,
+    /// only used in `RewardMode::Apr`, emission streams always split their rate linearly
+    pub accrual_mode: AccrualMode,
+    /// asset accepted by `Stake` and returned on unstake
+    pub stake_denom: Denom,
+    pub unbonding_period: u64,
+    /// lock durations a staker can choose from, on top of the implicit unlocked 1x tier
+    pub lock_tiers: Vec<LockTier>,
+    /// rewards accrue only between `rewards_start` and `rewards_end` when they are set
+    pub rewards_start: Option<Timestamp>,
+    pub rewards_end: Option<Timestamp>,
+    /// rejects `Stake` before `rewards_start`
+    pub stake_requires_start: bool,
+    pub max_total_staked: Option<Uint256>,
+    /// smallest position a staker can hold, unstaking everything is always allowed
+    pub min_stake_amount: Option<Uint256>,
+    pub max_stake_per_user: Option<Uint256>,
+    /// CW20 minted 1:1 for every staked token, positions are not transferable when unset
+    pub receipt_token: Option<Addr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LockTier {
+    /// lock duration in seconds
+    pub duration: u64,
+    /// reward weight of a locked position, `PERCENTS` is 1x
+    pub multiplier: Uint256,
+}
+
+/// A reward asset paid to every staker at its own APR
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RewardStream {
+    pub denom: Denom,
+    /// yearly rate as a fraction, `0.2` is 20%, used in `RewardMode::Apr`
+    pub apr: Decimal256,
+    /// tokens per second, used in `RewardMode::Emission`
+    pub emission_rate: Uint256,
+    /// emission stops here when set through a budget
+    pub emission_end: Option<Timestamp>,
+    /// APR index shaped by `Config.accrual_mode`, or the reward-per-share accumulator in
+    /// `RewardMode::Emission`
+    pub ci_current: Uint256,
+    pub total_withdrawn: Uint256,
+    /// rewards accrued by all stakers and not claimed yet, grows with `ci_current`
+    pub total_liability: Uint256,
+    /// tokens deposited through `FundRewards` and not paid out yet
+    pub reward_reserve: Uint256,
+    /// upcoming APR changes sorted by `effective_time`
+    pub apr_schedule: Vec<AprChange>,
+    /// removed streams stop accruing but accrued rewards stay claimable
+    pub active: bool,
+}
+
+/// APR a reward stream switches to at `effective_time`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AprChange {
+    pub effective_time: Timestamp,
+    pub apr: Decimal256,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct State {
     pub total_staked: Uint256,
-    pub total_withdrawn: Uint256,
-    pub ci_current: Uint256,
-    pub ci_time_current: Timestamp
+    pub total_unbonding: Uint256,
+    /// sum of every position's stake weighted by its lock multiplier, a lock's boost leaves it
+    /// at `lock_end` through `LOCK_EXPIRIES`
+    pub total_weighted_staked: Uint256,
+    /// number of positions with a non-zero stake
+    pub staker_count: u64,
+    pub ci_time_current: Timestamp,
+    pub reward_streams: Vec<RewardStream>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StakerReward {
+    pub ci_0: Uint256,
+    pub reward: Uint256,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct StakerInfo {
     pub staked: Uint256,
     pub block_time: Timestamp,
-    pub ci_0: Uint256,
-    pub reward: Uint256,
+    /// lock multiplier the position earns with until `lock_end`
+    pub multiplier: Uint256,
+    pub lock_end: Timestamp,
+    /// roll rewards in the staking asset into principal on every interaction
+    pub auto_compound: bool,
+    /// one entry per reward stream, in the order of `State.reward_streams`
+    pub rewards: Vec<StakerReward>,
 }
 
+/// Locks ending at the same second
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct LockExpiry {
+    /// weight the positions hold on top of their stake until the locks end
+    pub boost: Uint256,
+    /// positions that have not settled past the end of their lock yet
+    pub positions: u64,
+    /// every stream's index at the end of the locks, set once it passed
+    pub cis: Option<Vec<Uint256>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingWithdrawal {
+    pub amount: Uint256,
+    pub release_time: Timestamp,
+}
+
+/// Admin switch that halts staking and reward payouts
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct PauseInfo {
+    pub paused: bool,
+    pub reason: Option<String>,
+}
+
+/// Permission a privileged message requires, the owner (`ADMIN`) holds every role
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    /// configuration, role management and admin transfer
+    Owner,
+    /// APR changes
+    RateManager,
+    Pauser,
+    /// reward reserve funding and withdrawal
+    Treasurer,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Owner => "owner",
+            Role::RateManager => "rate_manager",
+            Role::Pauser => "pauser",
+            Role::Treasurer => "treasurer",
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Admin nominee that has to accept the role before `expiry`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingAdmin {
+    pub address: Addr,
+    pub expiry: Expiration,
+}
 
 pub const CONFIG: Item<Config> = Item::new("config");
 
@@ -36,4 +194,45 @@ pub const STATE: Item<State> = Item::new("state");
 
 pub const ADMIN: Item<Addr> = Item::new("admin");
 
+/// DAO the contract serves as voting module for, reported by the `Dao` query
+pub const DAO: Item<Addr> = Item::new("dao");
+
+pub const PENDING_ADMIN: Item<PendingAdmin> = Item::new("pending_admin");
+
+/// holders of every role but `Role::Owner`, keyed by `(role, address)`
+pub const ROLES: Map<(&str, Addr), Empty> = Map::new("roles");
+
+/// contracts sent a `StakeChangedHook` whenever a stake changes
+pub const HOOKS: Map<Addr, Empty> = Map::new("hooks");
+
 pub const USER_STAKINGS: Map<Addr, StakerInfo> = Map::new("user_stakings");
+
+/// owner of the receipts the receipt token is sending back, read by the `Receive` that follows
+pub const RECEIPT_SENDER: Item<Addr> = Item::new("receipt_sender");
+
+/// locks by the second they end at, keyed by `StakerInfo.lock_end`
+pub const LOCK_EXPIRIES: Map<u64, LockExpiry> = Map::new("lock_expiries");
+
+/// `USER_STAKINGS.staked` by block height, for voting power that cannot be bought after a
+/// proposal opens
+pub const STAKED_BALANCES: SnapshotMap<Addr, Uint256> = SnapshotMap::new(
+    "staked_balances",
+    "staked_balances__checkpoints",
+    "staked_balances__changelog",
+    Strategy::EveryBlock
+);
+
+/// `State.total_staked` by block height
+pub const STAKED_TOTAL: SnapshotItem<Uint256> = SnapshotItem::new(
+    "staked_total",
+    "staked_total__checkpoints",
+    "staked_total__changelog",
+    Strategy::EveryBlock
+);
+
+/// rewards every funder deposited through `FundRewards`, per denom
+pub const REWARD_FUNDERS: Map<Addr, Vec<RewardAmount>> = Map::new("reward_funders");
+
+pub const PENDING_WITHDRAWALS: Map<Addr, Vec<PendingWithdrawal>> = Map::new("pending_withdrawals");
+
+pub const PAUSE: Item<PauseInfo> = Item::new("pause");
@@ -1,47 +1,135 @@
 #[cfg(test)]
 mod tests {
     use cosmwasm_std::testing::mock_env;
-    use cosmwasm_std::{ Addr, BlockInfo, Coin, Timestamp, Uint128, Uint256 };
-    use cw_multi_test::{ App, Executor };
-    use crate::contract::ONE;
+    use cosmwasm_std::{
+        to_json_binary,
+        Addr,
+        BlockInfo,
+        Coin,
+        Decimal256,
+        Empty,
+        StdResult,
+        Timestamp,
+        Uint128,
+        Uint256,
+    };
+    use cw20::{
+        Cw20ExecuteMsg,
+        Cw20QueryMsg,
+        Cw20ReceiveMsg,
+        Denom,
+        TokenInfoResponse,
+        UncheckedDenom,
+    };
+    use cw_multi_test::{ App, AppResponse, Executor };
+    use cw_utils::Expiration;
     use crate::helpers::CwTemplateContract;
+    use crate::math::{
+        accrued_reward,
+        compound_index,
+        compounded_reward,
+        emission_index,
+        mul_div_floor,
+        simple_index,
+        ONE,
+    };
     use crate::integration_tests::tests::{
+        contract_template,
+        cw20_contract_template,
         expect_error,
+        hook_receiver_template,
+        instantiate_msg,
+        lock_tiers,
+        mock_app,
         proper_instantiate,
+        proper_instantiate_cw20,
+        proper_instantiate_with_msg,
+        proper_instantiate_with_reward,
+        query_cw20_balance,
+        receipt_token_template,
+        MockHookReceiverInstantiateMsg,
+        ADMIN,
         INJEX_TOKEN,
+        DAY,
+        UNBONDING_PERIOD,
         USDT,
         USER,
     };
-    use crate::msg::{ ExecuteMsg, QueryMsg };
-    use crate::state::{ StakerInfo, State, PERCENTS };
+    use crate::msg::{
+        ExecuteMsg,
+        InstantiateMsg,
+        QueryMsg,
+        ReceiptTokenMsg,
+        ReceiveMsg,
+        RemainingCapacityResponse,
+        ProjectedRewards,
+        RewardAmount,
+        SimulationResponse,
+        SolvencyResponse,
+        StakeChangedHookMsg,
+        StakedBalanceAtHeightResponse,
+        StakerSummary,
+        TotalStakedAtHeightResponse,
+    };
+    use crate::state::{
+        AccrualMode,
+        AprChange,
+        Config,
+        LockTier,
+        PauseInfo,
+        PendingAdmin,
+        PendingWithdrawal,
+        RewardMode,
+        RewardStream,
+        Role,
+        StakerInfo,
+        StakerReward,
+        State,
+        PERCENTS,
+    };
 
-    const APR: Uint256 = Uint256::from_u128(2_000_u128);
+    const APR: Decimal256 = Decimal256::percent(20);
     const SECONDS_IN_YEAR: Uint256 = Uint256::from_u128(31_536_000_u128);
 
-    fn calculate_ci(curr_ci: Uint256, apr: Uint256, time_elapsed: Uint256) -> Uint256 {
-        let new_ci =
-            (curr_ci * (ONE + (apr * time_elapsed * ONE) / (SECONDS_IN_YEAR * PERCENTS))) / ONE;
-
-        new_ci
+    fn calculate_ci(curr_ci: Uint256, apr: Decimal256, time_elapsed: Uint256) -> Uint256 {
+        curr_ci + (apr.atomics() * time_elapsed) / SECONDS_IN_YEAR
     }
 
     fn calculate_reward(tokens_staked: Uint256, ci_last: Uint256, ci_0: Uint256) -> Uint256 {
-        let reward = (tokens_staked * (ci_last - ci_0)) / ONE;
+        (tokens_staked * (ci_last - ci_0)) / ONE
+    }
+
+    fn query_apr(app: &App, contract: &CwTemplateContract) -> Decimal256 {
+        let reward_streams: Vec<RewardStream> = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &QueryMsg::GetRewardStreams {})
+            .unwrap();
 
-        reward
+        reward_streams[0].apr
     }
 
     #[test]
     fn proper_initialization() {
         let (app, contract) = proper_instantiate(true);
 
-        let token_msg = QueryMsg::GetInjexToken {};
-        let apr_msg = QueryMsg::GetApr {};
+        let stake_denom_msg = QueryMsg::GetStakeDenom {};
+        let streams_msg = QueryMsg::GetRewardStreams {};
 
-        let injex_token: Addr = app.wrap().query_wasm_smart(contract.addr(), &token_msg).unwrap();
-        let apr: Uint256 = app.wrap().query_wasm_smart(contract.addr(), &apr_msg).unwrap();
+        let stake_denom: Denom = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &stake_denom_msg)
+            .unwrap();
+        let reward_streams: Vec<RewardStream> = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &streams_msg)
+            .unwrap();
+        let apr = query_apr(&app, &contract);
 
-        assert_eq!(INJEX_TOKEN, injex_token.to_string());
+        assert_eq!(Denom::Native(INJEX_TOKEN.to_string()), stake_denom);
+        assert_eq!(reward_streams.len(), 1);
+        assert_eq!(Denom::Native(INJEX_TOKEN.to_string()), reward_streams[0].denom);
+        assert_eq!(reward_streams[0].ci_current, ONE);
+        assert!(reward_streams[0].active);
         assert_eq!(APR, apr);
     }
 
@@ -49,13 +137,13 @@ mod tests {
     fn stake_no_funds() {
         let (mut app, contract) = proper_instantiate(true);
 
-        let msg = ExecuteMsg::Stake {};
+        let msg = ExecuteMsg::Stake { lock_duration: None };
 
         let res = app.execute_contract(Addr::unchecked(USER), contract.addr(), &msg, &[]);
 
         assert!(res.is_err());
 
-        let error_message = format!("Invalid funds were provided");
+        let error_message = "Invalid funds were provided".to_string();
         expect_error(res, error_message);
     }
 
@@ -63,13 +151,13 @@ mod tests {
     fn stake_invalid_token() {
         let (mut app, contract) = proper_instantiate(true);
 
-        let msg = ExecuteMsg::Stake {};
+        let msg = ExecuteMsg::Stake { lock_duration: None };
 
         let res = app.execute_contract(
             Addr::unchecked(USER),
             contract.addr(),
             &msg,
-            &vec![Coin {
+            &[Coin {
                 denom: USDT.to_string(),
                 amount: Uint128::new(1_000_000),
             }]
@@ -77,7 +165,7 @@ mod tests {
 
         assert!(res.is_err());
 
-        let error_message = format!("Invalid coin passed in funds");
+        let error_message = "Invalid coin USDT passed in funds".to_string();
         expect_error(res, error_message);
     }
 
@@ -85,13 +173,13 @@ mod tests {
     fn stake_two_tokens() {
         let (mut app, contract) = proper_instantiate(true);
 
-        let msg = ExecuteMsg::Stake {};
+        let msg = ExecuteMsg::Stake { lock_duration: None };
 
         let res = app.execute_contract(
             Addr::unchecked(USER),
             contract.addr(),
             &msg,
-            &vec![
+            &[
                 Coin {
                     denom: USDT.to_string(),
                     amount: Uint128::new(1_000_000),
@@ -99,13 +187,13 @@ mod tests {
                 Coin {
                     denom: USDT.to_string(),
                     amount: Uint128::new(1_000_000),
-                }
+                },
             ]
         );
 
         assert!(res.is_err());
 
-        let error_message = format!("Invalid funds were provided");
+        let error_message = "Invalid funds were provided".to_string();
         expect_error(res, error_message);
     }
 
@@ -119,7 +207,7 @@ mod tests {
         let balance = app.wrap().query_balance(USER.to_string(), INJEX_TOKEN.to_string()).unwrap();
 
         let state_before: State = app.wrap().query_wasm_smart(contract.addr(), &state_msg).unwrap();
-        assert_eq!(state_before.ci_current, ONE);
+        assert_eq!(state_before.reward_streams[0].ci_current, ONE);
         assert_eq!(state_before.total_staked, Uint256::zero());
 
         stake_internal(&mut app, contract, stake_amount, true);
@@ -142,11 +230,10 @@ mod tests {
         let stake_amount = ONE;
         let block_time = mock_env().block.time;
         let state_msg = QueryMsg::GetState {};
-        let apr_msg = QueryMsg::GetApr {};
 
         let state_before: State = app.wrap().query_wasm_smart(contract.addr(), &state_msg).unwrap();
-        let apr: Uint256 = app.wrap().query_wasm_smart(contract.addr(), &apr_msg).unwrap();
-        assert_eq!(state_before.ci_current, ONE);
+        let apr = query_apr(&app, &contract);
+        assert_eq!(state_before.reward_streams[0].ci_current, ONE);
         assert_eq!(state_before.total_staked, Uint256::zero());
         let balance = app.wrap().query_balance(USER.to_string(), INJEX_TOKEN.to_string()).unwrap();
 
@@ -170,25 +257,25 @@ mod tests {
         );
 
         let new_ci = calculate_ci(
-            state.ci_current,
+            state.reward_streams[0].ci_current,
             apr,
             (new_block_time.seconds() - block_time.seconds()).into()
         );
-        let reward = calculate_reward(stake_amount, new_state.ci_current, user_staking.ci_0);
+        let reward = calculate_reward(stake_amount, new_state.reward_streams[0].ci_current, user_staking.rewards[0].ci_0);
 
-        assert_eq!(new_state.ci_current, new_ci);
+        assert_eq!(new_state.reward_streams[0].ci_current, new_ci);
         assert_eq!(new_state.ci_time_current, new_block_time);
         assert_eq!(new_state.total_staked, state.total_staked + stake_amount);
 
         assert_eq!(new_user_staking.block_time, block_time);
-        assert_eq!(new_user_staking.ci_0, new_state.ci_current);
+        assert_eq!(new_user_staking.rewards[0].ci_0, new_state.reward_streams[0].ci_current);
         assert_eq!(new_user_staking.staked, state.total_staked + stake_amount);
-        assert_eq!(new_user_staking.reward, reward);
+        assert_eq!(new_user_staking.rewards[0].reward, reward);
 
         let reward_calculated =
-            (stake_amount * Uint256::from_u128(200_u128) * apr) / (SECONDS_IN_YEAR * PERCENTS);
+            (stake_amount * Uint256::from_u128(200_u128) * apr) / SECONDS_IN_YEAR;
 
-        assert_eq!(reward_calculated, new_user_staking.reward);
+        assert_eq!(reward_calculated, new_user_staking.rewards[0].reward);
 
         let balance_after = app
             .wrap()
@@ -208,7 +295,6 @@ mod tests {
         let stake_amount = ONE + ONE;
         let block_time = mock_env().block.time;
         let state_msg = QueryMsg::GetState {};
-        let apr_msg = QueryMsg::GetApr {};
 
         let amount_to_unstake: Uint256 = stake_amount * Uint256::from_u128(2_u128);
 
@@ -217,12 +303,12 @@ mod tests {
 
         assert!(res.is_err());
 
-        let error_message = format!("No tokens were staked");
+        let error_message = "No tokens were staked".to_string();
         expect_error(res, error_message);
 
         let state_before: State = app.wrap().query_wasm_smart(contract.addr(), &state_msg).unwrap();
-        let apr: Uint256 = app.wrap().query_wasm_smart(contract.addr(), &apr_msg).unwrap();
-        assert_eq!(state_before.ci_current, ONE);
+        let apr = query_apr(&app, &contract);
+        assert_eq!(state_before.reward_streams[0].ci_current, ONE);
         assert_eq!(state_before.total_staked, Uint256::zero());
 
         let (user_staking, state) = stake_internal(&mut app, contract.clone(), stake_amount, true);
@@ -243,7 +329,10 @@ mod tests {
 
         assert!(res.is_err());
 
-        let error_message = format!("Insufficient balance");
+        let error_message = concat!(
+            "Insufficient balance: 2000000000000000000 staked, ",
+            "4000000000000000000 requested"
+        ).to_string();
         expect_error(res, error_message);
 
         let amount_to_unstake: Uint256 = stake_amount / Uint256::from_u128(2_u128);
@@ -261,20 +350,71 @@ mod tests {
         let new_state: State = app.wrap().query_wasm_smart(contract.addr(), &state_msg).unwrap();
 
         let new_ci = calculate_ci(
-            state.ci_current,
+            state.reward_streams[0].ci_current,
             apr,
             (new_block_time.seconds() - block_time.seconds()).into()
         );
-        let reward = calculate_reward(stake_amount, new_state.ci_current, user_staking.ci_0);
+        let reward = calculate_reward(stake_amount, new_state.reward_streams[0].ci_current, user_staking.rewards[0].ci_0);
 
-        assert_eq!(new_state.ci_current, new_ci);
+        assert_eq!(new_state.reward_streams[0].ci_current, new_ci);
         assert_eq!(new_state.ci_time_current, new_block_time);
         assert_eq!(new_state.total_staked, state.total_staked - amount_to_unstake);
 
         assert_eq!(new_user_staking.block_time, block_time);
-        assert_eq!(new_user_staking.ci_0, new_state.ci_current);
+        assert_eq!(new_user_staking.rewards[0].ci_0, new_state.reward_streams[0].ci_current);
         assert_eq!(new_user_staking.staked, state.total_staked - amount_to_unstake);
-        assert_eq!(new_user_staking.reward, reward);
+        assert_eq!(new_user_staking.rewards[0].reward, reward);
+        assert_eq!(new_state.total_unbonding, amount_to_unstake);
+
+        let pending_msg = QueryMsg::GetPendingWithdrawals { user: Addr::unchecked(USER) };
+        let pending: Vec<PendingWithdrawal> = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &pending_msg)
+            .unwrap();
+
+        assert_eq!(pending, vec![PendingWithdrawal {
+            amount: amount_to_unstake,
+            release_time: new_block_time.plus_seconds(UNBONDING_PERIOD),
+        }]);
+
+        let balance_after = app
+            .wrap()
+            .query_balance(USER.to_string(), INJEX_TOKEN.to_string())
+            .unwrap();
+
+        assert_eq!(balance_after.amount, balance.amount);
+
+        let withdraw_msg = ExecuteMsg::WithdrawUnbonded {};
+        let res = app.execute_contract(Addr::unchecked(USER), contract.addr(), &withdraw_msg, &[]);
+
+        assert!(res.is_err());
+
+        let error_message = "No unbonded tokens to withdraw".to_string();
+        expect_error(res, error_message);
+
+        let block_info = app.block_info();
+
+        app.set_block(BlockInfo {
+            chain_id: block_info.chain_id,
+            height: block_info.height + 1,
+            time: block_info.time.plus_seconds(UNBONDING_PERIOD),
+        });
+
+        let res = app.execute_contract(Addr::unchecked(USER), contract.addr(), &withdraw_msg, &[]);
+
+        assert!(res.is_ok());
+
+        let state_after_withdraw: State = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &state_msg)
+            .unwrap();
+        let pending: Vec<PendingWithdrawal> = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &pending_msg)
+            .unwrap();
+
+        assert_eq!(state_after_withdraw.total_unbonding, Uint256::zero());
+        assert!(pending.is_empty());
 
         let balance_after = app
             .wrap()
@@ -287,6 +427,100 @@ mod tests {
         );
     }
 
+    #[test]
+    fn withdraw_unbonded_partially_released() {
+        let (mut app, contract) = proper_instantiate(true);
+
+        let stake_amount = ONE + ONE;
+        let pending_msg = QueryMsg::GetPendingWithdrawals { user: Addr::unchecked(USER) };
+        let unstake_msg = ExecuteMsg::Unstake { amount: ONE };
+        let withdraw_msg = ExecuteMsg::WithdrawUnbonded {};
+
+        stake_internal(&mut app, contract.clone(), stake_amount, true);
+
+        let res = app.execute_contract(Addr::unchecked(USER), contract.addr(), &unstake_msg, &[]);
+        assert!(res.is_ok());
+
+        let block_info = app.block_info();
+
+        app.set_block(BlockInfo {
+            chain_id: block_info.chain_id,
+            height: block_info.height + 1,
+            time: block_info.time.plus_seconds(UNBONDING_PERIOD / 2),
+        });
+
+        let res = app.execute_contract(Addr::unchecked(USER), contract.addr(), &unstake_msg, &[]);
+        assert!(res.is_ok());
+
+        let second_release_time = app.block_info().time.plus_seconds(UNBONDING_PERIOD);
+
+        let block_info = app.block_info();
+
+        app.set_block(BlockInfo {
+            chain_id: block_info.chain_id,
+            height: block_info.height + 1,
+            time: block_info.time.plus_seconds(UNBONDING_PERIOD / 2),
+        });
+
+        let balance = app.wrap().query_balance(USER.to_string(), INJEX_TOKEN.to_string()).unwrap();
+
+        let res = app.execute_contract(Addr::unchecked(USER), contract.addr(), &withdraw_msg, &[]);
+        assert!(res.is_ok());
+
+        let balance_after = app
+            .wrap()
+            .query_balance(USER.to_string(), INJEX_TOKEN.to_string())
+            .unwrap();
+
+        assert_eq!(
+            Uint256::from_uint128(balance_after.amount),
+            Uint256::from_uint128(balance.amount) + ONE
+        );
+
+        let pending: Vec<PendingWithdrawal> = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &pending_msg)
+            .unwrap();
+
+        assert_eq!(pending, vec![PendingWithdrawal {
+            amount: ONE,
+            release_time: second_release_time,
+        }]);
+
+        let state: State = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &QueryMsg::GetState {})
+            .unwrap();
+
+        assert_eq!(state.total_staked, Uint256::zero());
+        assert_eq!(state.total_unbonding, ONE);
+    }
+
+    #[test]
+    fn change_unbonding_period() {
+        let (mut app, contract) = proper_instantiate(true);
+
+        let fake_user = "inj12vpajtjf5cvmk2w737m0t8qwwkyjz0xgvxwyus";
+        let msg = ExecuteMsg::ChangeUnbondingPeriod { new_unbonding_period: 86_400 };
+        let res = app.execute_contract(Addr::unchecked(fake_user), contract.addr(), &msg, &[]);
+
+        assert!(res.is_err());
+
+        let error_message = format!("Missing role owner for {}", fake_user);
+        expect_error(res, error_message);
+
+        let res = app.execute_contract(Addr::unchecked(USER), contract.addr(), &msg, &[]);
+
+        assert!(res.is_ok());
+
+        let unbonding_period: u64 = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &QueryMsg::GetUnbondingPeriod {})
+            .unwrap();
+
+        assert_eq!(unbonding_period, 86_400);
+    }
+
     #[test]
     fn claim_without_contract_balance() {
         let (mut app, contract) = proper_instantiate(false);
@@ -294,19 +528,18 @@ mod tests {
         let stake_amount = ONE;
         let block_time = mock_env().block.time;
         let state_msg = QueryMsg::GetState {};
-        let apr_msg = QueryMsg::GetApr {};
         let msg = ExecuteMsg::Claim {};
 
         let res = app.execute_contract(Addr::unchecked(USER), contract.addr(), &msg, &[]);
 
         assert!(res.is_err());
 
-        let error_message = format!("No claims");
+        let error_message = "No claims".to_string();
         expect_error(res, error_message);
 
         let state_before: State = app.wrap().query_wasm_smart(contract.addr(), &state_msg).unwrap();
-        let apr: Uint256 = app.wrap().query_wasm_smart(contract.addr(), &apr_msg).unwrap();
-        assert_eq!(state_before.ci_current, ONE);
+        let apr = query_apr(&app, &contract);
+        assert_eq!(state_before.reward_streams[0].ci_current, ONE);
         assert_eq!(state_before.total_staked, Uint256::zero());
 
         let (user_staking, state) = stake_internal(&mut app, contract.clone(), stake_amount, true);
@@ -329,25 +562,25 @@ mod tests {
         );
 
         let new_ci = calculate_ci(
-            state.ci_current,
+            state.reward_streams[0].ci_current,
             apr,
             (new_block_time.seconds() - block_time.seconds()).into()
         );
-        let reward = calculate_reward(stake_amount, new_state.ci_current, user_staking.ci_0);
+        let reward = calculate_reward(stake_amount, new_state.reward_streams[0].ci_current, user_staking.rewards[0].ci_0);
 
-        assert_eq!(new_state.ci_current, new_ci);
+        assert_eq!(new_state.reward_streams[0].ci_current, new_ci);
         assert_eq!(new_state.ci_time_current, new_block_time);
         assert_eq!(new_state.total_staked, stake_amount + stake_amount);
 
         assert_eq!(new_user_staking.block_time, block_time);
-        assert_eq!(new_user_staking.ci_0, new_state.ci_current);
+        assert_eq!(new_user_staking.rewards[0].ci_0, new_state.reward_streams[0].ci_current);
         assert_eq!(new_user_staking.staked, stake_amount + stake_amount);
-        assert_eq!(new_user_staking.reward, reward);
+        assert_eq!(new_user_staking.rewards[0].reward, reward);
 
         let reward_calculated =
-            (stake_amount * Uint256::from_u128(200_u128) * apr) / (SECONDS_IN_YEAR * PERCENTS);
+            (stake_amount * Uint256::from_u128(200_u128) * apr) / SECONDS_IN_YEAR;
 
-        assert_eq!(reward_calculated, new_user_staking.reward);
+        assert_eq!(reward_calculated, new_user_staking.rewards[0].reward);
 
         let block_info = app.block_info();
 
@@ -368,7 +601,7 @@ mod tests {
 
         assert!(res.is_err());
 
-        let error_message = format!("Insufficient contract balance");
+        let error_message = "Insufficient contract balance: INJX reserve 0 is below liability 7610350076102".to_string();
         expect_error(res, error_message);
     }
 
@@ -379,19 +612,18 @@ mod tests {
         let stake_amount = ONE;
         let block_time = mock_env().block.time;
         let state_msg = QueryMsg::GetState {};
-        let apr_msg = QueryMsg::GetApr {};
         let msg = ExecuteMsg::Claim {};
 
         let res = app.execute_contract(Addr::unchecked(USER), contract.addr(), &msg, &[]);
 
         assert!(res.is_err());
 
-        let error_message = format!("No claims");
+        let error_message = "No claims".to_string();
         expect_error(res, error_message);
 
         let state_before: State = app.wrap().query_wasm_smart(contract.addr(), &state_msg).unwrap();
-        let apr: Uint256 = app.wrap().query_wasm_smart(contract.addr(), &apr_msg).unwrap();
-        assert_eq!(state_before.ci_current, ONE);
+        let apr = query_apr(&app, &contract);
+        assert_eq!(state_before.reward_streams[0].ci_current, ONE);
         assert_eq!(state_before.total_staked, Uint256::zero());
 
         let (user_staking, state) = stake_internal(&mut app, contract.clone(), stake_amount, true);
@@ -414,25 +646,25 @@ mod tests {
         );
 
         let new_ci = calculate_ci(
-            state.ci_current,
+            state.reward_streams[0].ci_current,
             apr,
             (new_block_time.seconds() - block_time.seconds()).into()
         );
-        let reward = calculate_reward(stake_amount, new_state.ci_current, user_staking.ci_0);
+        let reward = calculate_reward(stake_amount, new_state.reward_streams[0].ci_current, user_staking.rewards[0].ci_0);
 
-        assert_eq!(new_state.ci_current, new_ci);
+        assert_eq!(new_state.reward_streams[0].ci_current, new_ci);
         assert_eq!(new_state.ci_time_current, new_block_time);
         assert_eq!(new_state.total_staked, stake_amount + stake_amount);
 
         assert_eq!(new_user_staking.block_time, block_time);
-        assert_eq!(new_user_staking.ci_0, new_state.ci_current);
+        assert_eq!(new_user_staking.rewards[0].ci_0, new_state.reward_streams[0].ci_current);
         assert_eq!(new_user_staking.staked, stake_amount + stake_amount);
-        assert_eq!(new_user_staking.reward, reward);
+        assert_eq!(new_user_staking.rewards[0].reward, reward);
 
         let reward_calculated =
-            (stake_amount * Uint256::from_u128(200_u128) * apr) / (SECONDS_IN_YEAR * PERCENTS);
+            (stake_amount * Uint256::from_u128(200_u128) * apr) / SECONDS_IN_YEAR;
 
-        assert_eq!(reward_calculated, new_user_staking.reward);
+        assert_eq!(reward_calculated, new_user_staking.rewards[0].reward);
 
         let block_info = app.block_info();
 
@@ -461,24 +693,24 @@ mod tests {
             .unwrap();
 
         let new_ci_claim = calculate_ci(
-            new_state.ci_current,
+            new_state.reward_streams[0].ci_current,
             apr,
             (new_block_time_claim.seconds() - new_block_time.seconds()).into()
         );
         let reward_claim = calculate_reward(
             stake_amount + stake_amount,
-            new_state_after_claim.ci_current,
-            new_user_staking.ci_0
+            new_state_after_claim.reward_streams[0].ci_current,
+            new_user_staking.rewards[0].ci_0
         );
 
-        assert_eq!(new_state_after_claim.ci_current, new_ci_claim);
+        assert_eq!(new_state_after_claim.reward_streams[0].ci_current, new_ci_claim);
         assert_eq!(new_state_after_claim.ci_time_current, new_block_time_claim);
-        assert_eq!(new_state_after_claim.total_withdrawn, reward_claim + reward);
+        assert_eq!(new_state_after_claim.reward_streams[0].total_withdrawn, reward_claim + reward);
 
         assert_eq!(new_user_staking_after_claim.block_time, block_time);
-        assert_eq!(new_user_staking_after_claim.ci_0, new_state_after_claim.ci_current);
+        assert_eq!(new_user_staking_after_claim.rewards[0].ci_0, new_state_after_claim.reward_streams[0].ci_current);
         assert_eq!(new_user_staking_after_claim.staked, new_state_after_claim.total_staked);
-        assert_eq!(new_user_staking_after_claim.reward, Uint256::zero());
+        assert_eq!(new_user_staking_after_claim.rewards[0].reward, Uint256::zero());
 
         let balance_after = app
             .wrap()
@@ -498,11 +730,10 @@ mod tests {
         let stake_amount = ONE;
         let block_time = mock_env().block.time;
         let state_msg = QueryMsg::GetState {};
-        let apr_msg = QueryMsg::GetApr {};
 
         let state_before: State = app.wrap().query_wasm_smart(contract.addr(), &state_msg).unwrap();
-        let apr: Uint256 = app.wrap().query_wasm_smart(contract.addr(), &apr_msg).unwrap();
-        assert_eq!(state_before.ci_current, ONE);
+        let apr = query_apr(&app, &contract);
+        assert_eq!(state_before.reward_streams[0].ci_current, ONE);
         assert_eq!(state_before.total_staked, Uint256::zero());
         let balance = app.wrap().query_balance(USER.to_string(), INJEX_TOKEN.to_string()).unwrap();
 
@@ -526,25 +757,25 @@ mod tests {
         );
 
         let new_ci = calculate_ci(
-            state.ci_current,
+            state.reward_streams[0].ci_current,
             apr,
             (new_block_time.seconds() - block_time.seconds()).into()
         );
-        let reward = calculate_reward(stake_amount, new_state.ci_current, user_staking.ci_0);
+        let reward = calculate_reward(stake_amount, new_state.reward_streams[0].ci_current, user_staking.rewards[0].ci_0);
 
-        assert_eq!(new_state.ci_current, new_ci);
+        assert_eq!(new_state.reward_streams[0].ci_current, new_ci);
         assert_eq!(new_state.ci_time_current, new_block_time);
         assert_eq!(new_state.total_staked, state.total_staked + stake_amount);
 
         assert_eq!(new_user_staking.block_time, block_time);
-        assert_eq!(new_user_staking.ci_0, new_state.ci_current);
+        assert_eq!(new_user_staking.rewards[0].ci_0, new_state.reward_streams[0].ci_current);
         assert_eq!(new_user_staking.staked, state.total_staked + stake_amount);
-        assert_eq!(new_user_staking.reward, reward);
+        assert_eq!(new_user_staking.rewards[0].reward, reward);
 
         let reward_calculated =
-            (stake_amount * Uint256::from_u128(200_u128) * apr) / (SECONDS_IN_YEAR * PERCENTS);
+            (stake_amount * Uint256::from_u128(200_u128) * apr) / SECONDS_IN_YEAR;
 
-        assert_eq!(reward_calculated, new_user_staking.reward);
+        assert_eq!(reward_calculated, new_user_staking.rewards[0].reward);
 
         let balance_after = app
             .wrap()
@@ -567,7 +798,10 @@ mod tests {
         let new_block_time_claim: Timestamp = app.block_info().time;
 
         let fake_user = "inj12vpajtjf5cvmk2w737m0t8qwwkyjz0xgvxwyus";
-        let change_apr_msg = ExecuteMsg::ChangeApr { new_apr: Uint256::zero() };
+        let change_apr_msg = ExecuteMsg::ChangeApr {
+            denom: UncheckedDenom::Native(INJEX_TOKEN.to_string()),
+            new_apr: Decimal256::zero(),
+        };
         let res = app.execute_contract(
             Addr::unchecked(fake_user),
             contract.addr(),
@@ -577,7 +811,7 @@ mod tests {
 
         assert!(res.is_err());
 
-        let error_message = format!("Only admin");
+        let error_message = format!("Missing role rate_manager for {}", fake_user);
         expect_error(res, error_message);
 
         let res = app.execute_contract(
@@ -589,10 +823,13 @@ mod tests {
 
         assert!(res.is_err());
 
-        let error_message = format!("Invalid APR");
+        let error_message = "Invalid APR".to_string();
         expect_error(res, error_message);
 
-        let change_apr_msg = ExecuteMsg::ChangeApr { new_apr: Uint256::from_u128(4000_u128) };
+        let change_apr_msg = ExecuteMsg::ChangeApr {
+            denom: UncheckedDenom::Native(INJEX_TOKEN.to_string()),
+            new_apr: Decimal256::percent(40),
+        };
         let res = app.execute_contract(
             Addr::unchecked(USER),
             contract.addr(),
@@ -608,14 +845,14 @@ mod tests {
             .unwrap();
 
         let new_ci_apr_change = calculate_ci(
-            new_state.ci_current,
+            new_state.reward_streams[0].ci_current,
             apr,
             (new_block_time_claim.seconds() - new_block_time.seconds()).into()
         );
 
-        assert_eq!(new_state_after_apr_change.ci_current, new_ci_apr_change);
+        assert_eq!(new_state_after_apr_change.reward_streams[0].ci_current, new_ci_apr_change);
         assert_eq!(new_state_after_apr_change.ci_time_current, new_block_time_claim);
-        assert_eq!(new_state_after_apr_change.total_withdrawn, Uint256::zero());
+        assert_eq!(new_state_after_apr_change.reward_streams[0].total_withdrawn, Uint256::zero());
 
         let block_info = app.block_info();
 
@@ -635,55 +872,3524 @@ mod tests {
         );
 
         let new_ci = calculate_ci(
-            new_state_after_apr_change.ci_current,
-            Uint256::from_u128(4000_u128),
+            new_state_after_apr_change.reward_streams[0].ci_current,
+            Decimal256::percent(40),
             (new_block_time_apr.seconds() - new_block_time_claim.seconds()).into()
         );
         let reward_apr_change = calculate_reward(
             stake_amount + stake_amount,
-            new_state.ci_current,
-            new_user_staking.ci_0
+            new_state.reward_streams[0].ci_current,
+            new_user_staking.rewards[0].ci_0
         );
 
-        assert_eq!(new_state.ci_current, new_ci);
+        assert_eq!(new_state.reward_streams[0].ci_current, new_ci);
         assert_eq!(new_state.ci_time_current, new_block_time_apr);
         assert_eq!(new_state.total_staked, stake_amount + stake_amount + stake_amount);
 
         assert_eq!(new_user_staking_after.block_time, block_time);
-        assert_eq!(new_user_staking_after.ci_0, new_state.ci_current);
-        assert_eq!(new_user_staking_after.reward, reward + reward_apr_change);
+        assert_eq!(new_user_staking_after.rewards[0].ci_0, new_state.reward_streams[0].ci_current);
+        assert_eq!(new_user_staking_after.rewards[0].reward, reward + reward_apr_change);
     }
 
     #[test]
-    fn change_injex_token() {
+    fn change_stake_denom() {
         let (mut app, contract) = proper_instantiate(true);
 
-        let new_token = "asdasd".to_string();
-
         let fake_user = "inj12vpajtjf5cvmk2w737m0t8qwwkyjz0xgvxwyus";
-        let change_token = ExecuteMsg::ChangeInjexToken { new_injex_token: "asdasd".to_string() };
+        let change_stake_denom = ExecuteMsg::ChangeStakeDenom {
+            new_stake_denom: UncheckedDenom::Native("asdasd".to_string()),
+        };
+
         let res = app.execute_contract(
             Addr::unchecked(fake_user),
             contract.addr(),
-            &change_token,
+            &change_stake_denom,
+            &[]
+        );
+
+        assert!(res.is_err());
+
+        let error_message = format!("Missing role owner for {}", fake_user);
+        expect_error(res, error_message);
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &change_stake_denom,
+            &[]
+        );
+
+        assert!(res.is_ok());
+
+        let stake_denom: Denom = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &QueryMsg::GetStakeDenom {})
+            .unwrap();
+
+        assert_eq!(stake_denom, Denom::Native("asdasd".to_string()));
+
+        // stakers are owed their principal in the denom they deposited
+        let (mut app, contract) = proper_instantiate(true);
+
+        stake_internal(&mut app, contract.clone(), ONE, true);
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &change_stake_denom,
+            &[]
+        );
+
+        expect_error(
+            res,
+            format!("Cannot change the stake denom while {} is staked or unbonding", ONE)
+        );
+
+        app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &(ExecuteMsg::Unstake { amount: ONE }),
+            &[]
+        ).unwrap();
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &change_stake_denom,
             &[]
         );
 
+        expect_error(
+            res,
+            format!("Cannot change the stake denom while {} is staked or unbonding", ONE)
+        );
+    }
+
+    #[test]
+    fn multiple_reward_streams() {
+        let (mut app, contract) = proper_instantiate(true);
+
+        let stake_amount = ONE;
+        let bonus_apr = Decimal256::percent(5);
+        let usdt = UncheckedDenom::Native(USDT.to_string());
+
+        let fake_user = "inj12vpajtjf5cvmk2w737m0t8qwwkyjz0xgvxwyus";
+        let add_msg = ExecuteMsg::AddRewardStream {
+            denom: usdt.clone(),
+            apr: bonus_apr,
+            emission_rate: Uint256::zero(),
+        };
+        let res = app.execute_contract(Addr::unchecked(fake_user), contract.addr(), &add_msg, &[]);
+
+        assert!(res.is_err());
+
+        let error_message = format!("Missing role owner for {}", fake_user);
+        expect_error(res, error_message);
+
+        stake_internal(&mut app, contract.clone(), stake_amount, true);
+
+        let block_info = app.block_info();
+
+        app.set_block(BlockInfo {
+            chain_id: block_info.chain_id,
+            height: block_info.height + 1,
+            time: block_info.time.plus_seconds(100),
+        });
+
+        let res = app.execute_contract(Addr::unchecked(USER), contract.addr(), &add_msg, &[]);
+
+        assert!(res.is_ok());
+
+        let res = app.execute_contract(Addr::unchecked(USER), contract.addr(), &add_msg, &[]);
+
         assert!(res.is_err());
 
-        let error_message = format!("Only admin");
+        let error_message = "Reward stream already exists".to_string();
         expect_error(res, error_message);
 
-        let change_token = ExecuteMsg::ChangeInjexToken { new_injex_token: new_token.clone() };
-        let res = app.execute_contract(Addr::unchecked(USER), contract.addr(), &change_token, &[]);
+        app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &ExecuteMsg::FundRewards {},
+            &[
+                Coin {
+                    denom: USDT.to_string(),
+                    amount: Uint128::new(1000000000000000000),
+                },
+            ]
+        ).unwrap();
+
+        let block_info = app.block_info();
+
+        app.set_block(BlockInfo {
+            chain_id: block_info.chain_id,
+            height: block_info.height + 1,
+            time: block_info.time.plus_seconds(200),
+        });
+
+        // INJX accrued for the whole 300 seconds, USDT only since the stream was added
+        let injex_ci = calculate_ci(calculate_ci(ONE, APR, 100_u128.into()), APR, 200_u128.into());
+        let injex_reward = calculate_reward(stake_amount, injex_ci, ONE);
+        let usdt_reward = calculate_reward(
+            stake_amount,
+            calculate_ci(ONE, bonus_apr, 200_u128.into()),
+            ONE
+        );
+
+        let claimable: Vec<RewardAmount> = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &(QueryMsg::GetClaimableAmount {
+                user: Addr::unchecked(USER),
+            }))
+            .unwrap();
+
+        assert_eq!(claimable, vec![
+            RewardAmount {
+                denom: Denom::Native(INJEX_TOKEN.to_string()),
+                amount: injex_reward,
+            },
+            RewardAmount {
+                denom: Denom::Native(USDT.to_string()),
+                amount: usdt_reward,
+            },
+        ]);
+
+        let injex_balance = app
+            .wrap()
+            .query_balance(USER.to_string(), INJEX_TOKEN.to_string())
+            .unwrap();
+        let usdt_balance = app.wrap().query_balance(USER.to_string(), USDT.to_string()).unwrap();
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &ExecuteMsg::Claim {},
+            &[]
+        );
+
+        assert!(res.is_ok());
+
+        let injex_balance_after = app
+            .wrap()
+            .query_balance(USER.to_string(), INJEX_TOKEN.to_string())
+            .unwrap();
+        let usdt_balance_after = app
+            .wrap()
+            .query_balance(USER.to_string(), USDT.to_string())
+            .unwrap();
+
+        assert_eq!(
+            Uint256::from_uint128(injex_balance_after.amount),
+            Uint256::from_uint128(injex_balance.amount) + injex_reward
+        );
+        assert_eq!(
+            Uint256::from_uint128(usdt_balance_after.amount),
+            Uint256::from_uint128(usdt_balance.amount) + usdt_reward
+        );
+
+        // a removed stream stops accruing but keeps its history
+        let remove_msg = ExecuteMsg::RemoveRewardStream { denom: usdt.clone() };
+        let res = app.execute_contract(Addr::unchecked(USER), contract.addr(), &remove_msg, &[]);
 
         assert!(res.is_ok());
 
-        let token_msg = QueryMsg::GetInjexToken {};
+        let res = app.execute_contract(Addr::unchecked(USER), contract.addr(), &remove_msg, &[]);
+
+        assert!(res.is_err());
+
+        let error_message = "Reward stream USDT not found".to_string();
+        expect_error(res, error_message);
+
+        let block_info = app.block_info();
+
+        app.set_block(BlockInfo {
+            chain_id: block_info.chain_id,
+            height: block_info.height + 1,
+            time: block_info.time.plus_seconds(200),
+        });
+
+        let claimable: Vec<RewardAmount> = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &(QueryMsg::GetClaimableAmount {
+                user: Addr::unchecked(USER),
+            }))
+            .unwrap();
+
+        assert!(claimable[0].amount > Uint256::zero());
+        assert_eq!(claimable[1].amount, Uint256::zero());
+
+        let total_withdrawn: Vec<RewardAmount> = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &QueryMsg::GetTotalWithdrawn {})
+            .unwrap();
 
-        let token: String = app.wrap().query_wasm_smart(contract.addr(), &token_msg).unwrap();
+        assert_eq!(total_withdrawn[1].amount, usdt_reward);
 
-        assert_eq!(token, new_token.clone());
+        let reward_streams: Vec<RewardStream> = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &QueryMsg::GetRewardStreams {})
+            .unwrap();
+
+        assert!(!reward_streams[1].active);
+    }
+
+    #[test]
+    fn claim_separate_reward_denom() {
+        let (mut app, contract) = proper_instantiate_with_reward(USDT, false);
+
+        let stake_amount = ONE;
+
+        stake_internal(&mut app, contract.clone(), stake_amount, true);
+
+        let block_info = app.block_info();
+
+        app.set_block(BlockInfo {
+            chain_id: block_info.chain_id,
+            height: block_info.height + 1,
+            time: block_info.time.plus_seconds(200),
+        });
+
+        // staked INJX must not be counted as a USDT reward reserve
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &ExecuteMsg::Claim {},
+            &[]
+        );
+
+        assert!(res.is_err());
+
+        let error_message = "Insufficient contract balance: USDT reserve 0 is below liability 1268391679350".to_string();
+        expect_error(res, error_message);
+
+        let reward = calculate_reward(stake_amount, calculate_ci(ONE, APR, 200_u128.into()), ONE);
+
+        app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &ExecuteMsg::FundRewards {},
+            &[
+                Coin {
+                    denom: USDT.to_string(),
+                    amount: Uint128::try_from(reward).unwrap(),
+                },
+            ]
+        ).unwrap();
+
+        let injex_balance = app
+            .wrap()
+            .query_balance(USER.to_string(), INJEX_TOKEN.to_string())
+            .unwrap();
+        let usdt_balance = app.wrap().query_balance(USER.to_string(), USDT.to_string()).unwrap();
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &ExecuteMsg::Claim {},
+            &[]
+        );
+
+        assert!(res.is_ok());
+
+        let injex_balance_after = app
+            .wrap()
+            .query_balance(USER.to_string(), INJEX_TOKEN.to_string())
+            .unwrap();
+        let usdt_balance_after = app
+            .wrap()
+            .query_balance(USER.to_string(), USDT.to_string())
+            .unwrap();
+
+        assert_eq!(injex_balance_after.amount, injex_balance.amount);
+        assert_eq!(
+            Uint256::from_uint128(usdt_balance_after.amount),
+            Uint256::from_uint128(usdt_balance.amount) + reward
+        );
+    }
+
+    #[test]
+    fn stake_with_lock_tier() {
+        let (mut app, contract) = proper_instantiate(true);
+
+        let stake_amount = ONE;
+        let multiplier = Uint256::from_u128(12_500_u128);
+        let weighted_amount = (stake_amount * multiplier) / PERCENTS;
+        let user_staking_msg = QueryMsg::GetStakerInfo { user: Addr::unchecked(USER) };
+        let state_msg = QueryMsg::GetState {};
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &(ExecuteMsg::Stake { lock_duration: Some(7 * DAY) }),
+            &[Coin {
+                denom: INJEX_TOKEN.to_string(),
+                amount: Uint128::try_from(stake_amount).unwrap(),
+            }]
+        );
+
+        assert!(res.is_err());
+
+        let error_message = "Invalid lock tier".to_string();
+        expect_error(res, error_message);
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &(ExecuteMsg::Stake { lock_duration: Some(30 * DAY) }),
+            &[Coin {
+                denom: INJEX_TOKEN.to_string(),
+                amount: Uint128::try_from(stake_amount).unwrap(),
+            }]
+        );
+
+        assert!(res.is_ok());
+
+        let lock_end = app.block_info().time.plus_seconds(30 * DAY);
+
+        let user_staking: StakerInfo = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &user_staking_msg)
+            .unwrap();
+        let state: State = app.wrap().query_wasm_smart(contract.addr(), &state_msg).unwrap();
+
+        assert_eq!(user_staking.multiplier, multiplier);
+        assert_eq!(user_staking.lock_end, lock_end);
+        assert_eq!(state.total_weighted_staked, weighted_amount);
+
+        let block_info = app.block_info();
+
+        app.set_block(BlockInfo {
+            chain_id: block_info.chain_id,
+            height: block_info.height + 1,
+            time: block_info.time.plus_seconds(200),
+        });
+
+        let claimable: Vec<RewardAmount> = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &(QueryMsg::GetClaimableAmount {
+                user: Addr::unchecked(USER),
+            }))
+            .unwrap();
+
+        assert_eq!(
+            claimable[0].amount,
+            calculate_reward(weighted_amount, calculate_ci(ONE, APR, 200_u128.into()), ONE)
+        );
+
+        let unstake_msg = ExecuteMsg::Unstake { amount: stake_amount };
+        let res = app.execute_contract(Addr::unchecked(USER), contract.addr(), &unstake_msg, &[]);
+
+        assert!(res.is_err());
+
+        let error_message = format!("Stake is locked until {}", lock_end.seconds());
+        expect_error(res, error_message);
+
+        let block_info = app.block_info();
+
+        app.set_block(BlockInfo {
+            chain_id: block_info.chain_id,
+            height: block_info.height + 1,
+            time: lock_end,
+        });
+
+        let res = app.execute_contract(Addr::unchecked(USER), contract.addr(), &unstake_msg, &[]);
+
+        assert!(res.is_ok());
+
+        let user_staking: StakerInfo = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &user_staking_msg)
+            .unwrap();
+        let state: State = app.wrap().query_wasm_smart(contract.addr(), &state_msg).unwrap();
+
+        assert_eq!(user_staking.multiplier, PERCENTS);
+        assert_eq!(user_staking.staked, Uint256::zero());
+        assert_eq!(state.total_weighted_staked, Uint256::zero());
+    }
+
+    #[test]
+    fn lock_boost_ends_at_lock_end() {
+        let (mut app, contract) = proper_instantiate(true);
+
+        let other = Addr::unchecked("inj12vpajtjf5cvmk2w737m0t8qwwkyjz0xgvxwyus");
+        let multiplier = Uint256::from_u128(12_500_u128);
+        let weighted_amount = (ONE * multiplier) / PERCENTS;
+        let funds = vec![Coin {
+            denom: INJEX_TOKEN.to_string(),
+            amount: Uint128::try_from(ONE).unwrap(),
+        }];
+
+        app.send_tokens(Addr::unchecked(USER), other.clone(), &funds).unwrap();
+
+        app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &(ExecuteMsg::Stake { lock_duration: Some(30 * DAY) }),
+            &funds
+        ).unwrap();
+        app.execute_contract(
+            other.clone(),
+            contract.addr(),
+            &(ExecuteMsg::Stake { lock_duration: None }),
+            &funds
+        ).unwrap();
+
+        // nobody touches the contract for a year, the lock ended after 30 days of it
+        let block_info = app.block_info();
+
+        app.set_block(BlockInfo {
+            chain_id: block_info.chain_id,
+            height: block_info.height + 1,
+            time: block_info.time.plus_seconds(365 * DAY),
+        });
+
+        let ci_lock_end = calculate_ci(ONE, APR, (30 * DAY).into());
+        let ci_now = calculate_ci(ci_lock_end, APR, (335 * DAY).into());
+        let expected =
+            calculate_reward(weighted_amount, ci_lock_end, ONE) +
+            calculate_reward(ONE, ci_now, ci_lock_end);
+
+        let claimable: Vec<RewardAmount> = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &(QueryMsg::GetClaimableAmount {
+                user: Addr::unchecked(USER),
+            }))
+            .unwrap();
+
+        assert_eq!(claimable[0].amount, expected);
+
+        // the first interaction after the lock ended takes its boost out of the pool weight
+        app.execute_contract(
+            other.clone(),
+            contract.addr(),
+            &(ExecuteMsg::Claim {}),
+            &[]
+        ).unwrap();
+
+        let state: State = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &(QueryMsg::GetState {}))
+            .unwrap();
+
+        assert_eq!(state.total_weighted_staked, ONE + ONE);
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &(ExecuteMsg::Claim {}),
+            &[]
+        ).unwrap();
+
+        let claimed = res.events
+            .iter()
+            .flat_map(|event| event.attributes.iter())
+            .find(|attribute| attribute.key == "amount_claimed")
+            .map(|attribute| attribute.value.clone());
+
+        assert_eq!(claimed, Some(format!("{}{}", expected, INJEX_TOKEN)));
+
+        // tokens added to a running lock without locking them keep only the boost it holds
+        app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &(ExecuteMsg::Stake { lock_duration: Some(30 * DAY) }),
+            &funds
+        ).unwrap();
+
+        let lock_end = app.block_info().time.plus_seconds(30 * DAY);
+
+        app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &(ExecuteMsg::Stake { lock_duration: None }),
+            &funds
+        ).unwrap();
+
+        let user_staking: StakerInfo = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &(QueryMsg::GetStakerInfo {
+                user: Addr::unchecked(USER),
+            }))
+            .unwrap();
+        let state: State = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &(QueryMsg::GetState {}))
+            .unwrap();
+
+        // 3.5 weight over 3 tokens, rounded down to the basis point
+        let topped_up_multiplier = Uint256::from_u128(11_666_u128);
+        let staked = ONE + ONE + ONE;
+
+        assert_eq!(user_staking.staked, staked);
+        assert_eq!(user_staking.multiplier, topped_up_multiplier);
+        assert_eq!(user_staking.lock_end, lock_end);
+        assert_eq!(
+            state.total_weighted_staked,
+            (staked * topped_up_multiplier) / PERCENTS + ONE
+        );
+    }
+
+    #[test]
+    fn update_lock_tiers() {
+        let (mut app, contract) = proper_instantiate(true);
+
+        let fake_user = "inj12vpajtjf5cvmk2w737m0t8qwwkyjz0xgvxwyus";
+        let new_tiers = vec![LockTier {
+            duration: 365 * DAY,
+            multiplier: Uint256::from_u128(30_000_u128),
+        }];
+
+        let msg = ExecuteMsg::UpdateLockTiers { lock_tiers: new_tiers.clone() };
+        let res = app.execute_contract(Addr::unchecked(fake_user), contract.addr(), &msg, &[]);
+
+        assert!(res.is_err());
+
+        let error_message = format!("Missing role owner for {}", fake_user);
+        expect_error(res, error_message);
+
+        let invalid_msg = ExecuteMsg::UpdateLockTiers {
+            lock_tiers: vec![LockTier {
+                duration: 30 * DAY,
+                multiplier: Uint256::from_u128(5_000_u128),
+            }],
+        };
+        let res = app.execute_contract(Addr::unchecked(USER), contract.addr(), &invalid_msg, &[]);
+
+        assert!(res.is_err());
+
+        let error_message = "Invalid lock tier".to_string();
+        expect_error(res, error_message);
+
+        // a multiplier past 10x could overflow every position's weight
+        let invalid_msg = ExecuteMsg::UpdateLockTiers {
+            lock_tiers: vec![LockTier {
+                duration: 30 * DAY,
+                multiplier: Uint256::MAX,
+            }],
+        };
+        let res = app.execute_contract(Addr::unchecked(USER), contract.addr(), &invalid_msg, &[]);
+
+        expect_error(res, "Invalid lock tier".to_string());
+
+        let tiers: Vec<LockTier> = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &QueryMsg::GetLockTiers {})
+            .unwrap();
+
+        assert_eq!(tiers, lock_tiers());
+
+        let res = app.execute_contract(Addr::unchecked(USER), contract.addr(), &msg, &[]);
+
+        assert!(res.is_ok());
+
+        let tiers: Vec<LockTier> = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &QueryMsg::GetLockTiers {})
+            .unwrap();
+
+        assert_eq!(tiers, new_tiers);
+    }
+
+    #[test]
+    fn compound() {
+        let (mut app, contract) = proper_instantiate(true);
+
+        let stake_amount = ONE;
+        let user_staking_msg = QueryMsg::GetStakerInfo { user: Addr::unchecked(USER) };
+        let state_msg = QueryMsg::GetState {};
+        let compound_msg = ExecuteMsg::Compound {};
+
+        stake_internal(&mut app, contract.clone(), stake_amount, true);
+
+        let res = app.execute_contract(Addr::unchecked(USER), contract.addr(), &compound_msg, &[]);
+
+        assert!(res.is_err());
+
+        let error_message = "No claims".to_string();
+        expect_error(res, error_message);
+
+        let block_info = app.block_info();
+
+        app.set_block(BlockInfo {
+            chain_id: block_info.chain_id,
+            height: block_info.height + 1,
+            time: block_info.time.plus_seconds(200),
+        });
+
+        let balance = app.wrap().query_balance(USER.to_string(), INJEX_TOKEN.to_string()).unwrap();
+
+        let res = app.execute_contract(Addr::unchecked(USER), contract.addr(), &compound_msg, &[]);
+
+        assert!(res.is_ok());
+
+        let reward = calculate_reward(stake_amount, calculate_ci(ONE, APR, 200_u128.into()), ONE);
+
+        let user_staking: StakerInfo = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &user_staking_msg)
+            .unwrap();
+        let state: State = app.wrap().query_wasm_smart(contract.addr(), &state_msg).unwrap();
+
+        assert_eq!(user_staking.staked, stake_amount + reward);
+        assert_eq!(user_staking.rewards[0].reward, Uint256::zero());
+        assert_eq!(state.total_staked, stake_amount + reward);
+        assert_eq!(state.total_weighted_staked, stake_amount + reward);
+        assert_eq!(state.reward_streams[0].total_withdrawn, reward);
+
+        // nothing leaves the contract
+        let balance_after = app
+            .wrap()
+            .query_balance(USER.to_string(), INJEX_TOKEN.to_string())
+            .unwrap();
+
+        assert_eq!(balance_after.amount, balance.amount);
+    }
+
+    #[test]
+    fn auto_compound() {
+        let (mut app, contract) = proper_instantiate(true);
+
+        let stake_amount = ONE;
+        let user_staking_msg = QueryMsg::GetStakerInfo { user: Addr::unchecked(USER) };
+
+        // an address without a position is not turned into an empty staker entry
+        let res = app.execute_contract(
+            Addr::unchecked("inj12vpajtjf5cvmk2w737m0t8qwwkyjz0xgvxwyus"),
+            contract.addr(),
+            &(ExecuteMsg::SetAutoCompound { enabled: true }),
+            &[]
+        );
+
+        expect_error(res, "No staking position".to_string());
+
+        stake_internal(&mut app, contract.clone(), stake_amount, true);
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &(ExecuteMsg::SetAutoCompound { enabled: true }),
+            &[]
+        );
+
+        assert!(res.is_ok());
+
+        let page: Vec<StakerSummary> = app
+            .wrap()
+            .query_wasm_smart(
+                contract.addr(),
+                &(QueryMsg::ListStakers { start_after: None, limit: None })
+            )
+            .unwrap();
+
+        assert_eq!(page.len(), 1);
+
+        let block_info = app.block_info();
+
+        app.set_block(BlockInfo {
+            chain_id: block_info.chain_id,
+            height: block_info.height + 1,
+            time: block_info.time.plus_seconds(200),
+        });
+
+        let (user_staking, state) = stake_internal(&mut app, contract.clone(), stake_amount, false);
+
+        let reward = calculate_reward(stake_amount, calculate_ci(ONE, APR, 200_u128.into()), ONE);
+
+        assert!(user_staking.auto_compound);
+        assert_eq!(user_staking.staked, stake_amount + stake_amount + reward);
+        assert_eq!(user_staking.rewards[0].reward, Uint256::zero());
+        assert_eq!(state.total_staked, stake_amount + stake_amount + reward);
+
+        let block_info = app.block_info();
+
+        app.set_block(BlockInfo {
+            chain_id: block_info.chain_id,
+            height: block_info.height + 1,
+            time: block_info.time.plus_seconds(200),
+        });
+
+        // claim rolls the staking asset rewards into principal instead of paying them out
+        let balance = app.wrap().query_balance(USER.to_string(), INJEX_TOKEN.to_string()).unwrap();
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &ExecuteMsg::Claim {},
+            &[]
+        );
+
+        assert!(res.is_ok());
+
+        let balance_after = app
+            .wrap()
+            .query_balance(USER.to_string(), INJEX_TOKEN.to_string())
+            .unwrap();
+        let user_staking_after: StakerInfo = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &user_staking_msg)
+            .unwrap();
+
+        let state_after: State = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &QueryMsg::GetState {})
+            .unwrap();
+
+        let reward = calculate_reward(
+            user_staking.staked,
+            state_after.reward_streams[0].ci_current,
+            user_staking.rewards[0].ci_0
+        );
+
+        assert_eq!(balance_after.amount, balance.amount);
+        assert!(user_staking_after.staked > user_staking.staked);
+        assert_eq!(user_staking_after.rewards[0].reward, Uint256::zero());
+        assert_eq!(user_staking_after.staked, user_staking.staked + reward);
+    }
+
+    #[test]
+    fn compound_unavailable_for_other_reward_denom() {
+        let (mut app, contract) = proper_instantiate_with_reward(USDT, true);
+
+        stake_internal(&mut app, contract.clone(), ONE, true);
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &ExecuteMsg::Compound {},
+            &[]
+        );
+
+        assert!(res.is_err());
+
+        let error_message = "No reward stream pays out the staking asset".to_string();
+        expect_error(res, error_message);
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &(ExecuteMsg::SetAutoCompound { enabled: true }),
+            &[]
+        );
+
+        assert!(res.is_err());
+
+        let error_message = "No reward stream pays out the staking asset".to_string();
+        expect_error(res, error_message);
+    }
+
+    #[test]
+    fn stake_cw20() {
+        let (mut app, contract, token) = proper_instantiate_cw20(true);
+
+        let stake_amount = ONE;
+        let state_msg = QueryMsg::GetState {};
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &ExecuteMsg::Stake { lock_duration: None },
+            &[Coin {
+                denom: INJEX_TOKEN.to_string(),
+                amount: Uint128::new(1_000_000),
+            }]
+        );
+
+        assert!(res.is_err());
+
+        let error_message = "Invalid coin INJX passed in funds".to_string();
+        expect_error(res, error_message);
+
+        // the hook can only be called by the configured token contract
+        let fake_receive = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: USER.to_string(),
+            amount: Uint128::try_from(stake_amount).unwrap(),
+            msg: to_json_binary(&ReceiveMsg::Stake { lock_duration: None }).unwrap(),
+        });
+        let res = app.execute_contract(Addr::unchecked(USER), contract.addr(), &fake_receive, &[]);
+
+        assert!(res.is_err());
+
+        let error_message = format!("Invalid coin {} passed in funds", USER);
+        expect_error(res, error_message);
+
+        let balance = query_cw20_balance(&app, &token, USER);
+
+        stake_cw20_internal(&mut app, &contract, &token, stake_amount);
+
+        let state: State = app.wrap().query_wasm_smart(contract.addr(), &state_msg).unwrap();
+        let user_staking: StakerInfo = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &(QueryMsg::GetStakerInfo {
+                user: Addr::unchecked(USER),
+            }))
+            .unwrap();
+
+        assert_eq!(state.total_staked, stake_amount);
+        assert_eq!(user_staking.staked, stake_amount);
+        assert_eq!(
+            Uint256::from_uint128(query_cw20_balance(&app, &token, USER)),
+            Uint256::from_uint128(balance) - stake_amount
+        );
+    }
+
+    #[test]
+    fn claim_and_withdraw_cw20() {
+        let (mut app, contract, token) = proper_instantiate_cw20(true);
+
+        let stake_amount = ONE;
+
+        stake_cw20_internal(&mut app, &contract, &token, stake_amount);
+
+        let block_info = app.block_info();
+
+        app.set_block(BlockInfo {
+            chain_id: block_info.chain_id,
+            height: block_info.height + 1,
+            time: block_info.time.plus_seconds(200),
+        });
+
+        let balance = query_cw20_balance(&app, &token, USER);
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &ExecuteMsg::Claim {},
+            &[]
+        );
+
+        assert!(res.is_ok());
+
+        let reward = calculate_reward(stake_amount, calculate_ci(ONE, APR, 200_u128.into()), ONE);
+
+        assert_eq!(
+            Uint256::from_uint128(query_cw20_balance(&app, &token, USER)),
+            Uint256::from_uint128(balance) + reward
+        );
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &(ExecuteMsg::Unstake { amount: stake_amount }),
+            &[]
+        );
+
+        assert!(res.is_ok());
+
+        let block_info = app.block_info();
+
+        app.set_block(BlockInfo {
+            chain_id: block_info.chain_id,
+            height: block_info.height + 1,
+            time: block_info.time.plus_seconds(UNBONDING_PERIOD),
+        });
+
+        let balance = query_cw20_balance(&app, &token, USER);
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &ExecuteMsg::WithdrawUnbonded {},
+            &[]
+        );
+
+        assert!(res.is_ok());
+
+        assert_eq!(
+            Uint256::from_uint128(query_cw20_balance(&app, &token, USER)),
+            Uint256::from_uint128(balance) + stake_amount
+        );
+    }
+
+    #[test]
+    fn pause_blocks_stake_and_claim() {
+        let (mut app, contract) = proper_instantiate(true);
+
+        let stake_amount = ONE;
+        let pause_msg = ExecuteMsg::Pause { reason: Some("ci overflow".to_string()) };
+        let pause_info_msg = QueryMsg::GetPauseInfo {};
+
+        stake_internal(&mut app, contract.clone(), stake_amount, true);
+
+        let res = app.execute_contract(
+            Addr::unchecked("inj12vpajtjf5cvmk2w737m0t8qwwkyjz0xgvxwyus"),
+            contract.addr(),
+            &pause_msg,
+            &[]
+        );
+
+        expect_error(
+            res,
+            "Missing role pauser for inj12vpajtjf5cvmk2w737m0t8qwwkyjz0xgvxwyus".to_string()
+        );
+
+        let res = app.execute_contract(Addr::unchecked(USER), contract.addr(), &pause_msg, &[]);
+
+        assert!(res.is_ok());
+
+        let pause_info: PauseInfo = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &pause_info_msg)
+            .unwrap();
+
+        assert!(pause_info.paused);
+        assert_eq!(pause_info.reason, Some("ci overflow".to_string()));
+
+        let block_info = app.block_info();
+
+        app.set_block(BlockInfo {
+            chain_id: block_info.chain_id,
+            height: block_info.height + 1,
+            time: block_info.time.plus_seconds(200),
+        });
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &(ExecuteMsg::Stake { lock_duration: None }),
+            &[Coin {
+                denom: INJEX_TOKEN.to_string(),
+                amount: Uint128::new(1_000_000),
+            }]
+        );
+
+        expect_error(res, "Contract is paused".to_string());
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &(ExecuteMsg::Claim {}),
+            &[]
+        );
+
+        expect_error(res, "Contract is paused".to_string());
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &(ExecuteMsg::Unpause {}),
+            &[]
+        );
+
+        assert!(res.is_ok());
+
+        let pause_info: PauseInfo = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &pause_info_msg)
+            .unwrap();
+
+        assert_eq!(pause_info, PauseInfo::default());
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &(ExecuteMsg::Claim {}),
+            &[]
+        );
+
+        assert!(res.is_ok());
+    }
+
+    #[test]
+    fn emergency_withdraw_while_paused() {
+        let (mut app, contract) = proper_instantiate(true);
+
+        let stake_amount = ONE;
+        let unstake_amount = ONE / Uint256::from_u128(4_u128);
+        let emergency_msg = ExecuteMsg::EmergencyWithdraw {};
+
+        let balance = app.wrap().query_balance(USER.to_string(), INJEX_TOKEN.to_string()).unwrap();
+
+        stake_internal(&mut app, contract.clone(), stake_amount, true);
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &(ExecuteMsg::Unstake { amount: unstake_amount }),
+            &[]
+        );
+
+        assert!(res.is_ok());
+
+        let block_info = app.block_info();
+
+        app.set_block(BlockInfo {
+            chain_id: block_info.chain_id,
+            height: block_info.height + 1,
+            time: block_info.time.plus_seconds(200),
+        });
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &(ExecuteMsg::Pause { reason: None }),
+            &[]
+        );
+
+        assert!(res.is_ok());
+
+        let res = app.execute_contract(Addr::unchecked(USER), contract.addr(), &emergency_msg, &[]);
+
+        assert!(res.is_ok());
+
+        // principal and unbonding tokens come back, accrued rewards are forfeited
+        let balance_after = app
+            .wrap()
+            .query_balance(USER.to_string(), INJEX_TOKEN.to_string())
+            .unwrap();
+
+        assert_eq!(balance_after.amount, balance.amount);
+
+        let state: State = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &QueryMsg::GetState {})
+            .unwrap();
+        let pending: Vec<PendingWithdrawal> = app
+            .wrap()
+            .query_wasm_smart(
+                contract.addr(),
+                &(QueryMsg::GetPendingWithdrawals { user: Addr::unchecked(USER) })
+            )
+            .unwrap();
+
+        assert_eq!(state.total_staked, Uint256::zero());
+        assert_eq!(state.total_unbonding, Uint256::zero());
+        assert_eq!(state.total_weighted_staked, Uint256::zero());
+        assert!(pending.is_empty());
+
+        let res = app.execute_contract(Addr::unchecked(USER), contract.addr(), &emergency_msg, &[]);
+
+        expect_error(res, "No unbonded tokens to withdraw".to_string());
+    }
+
+    #[test]
+    fn emergency_withdraw_unbonds_when_not_paused() {
+        let (mut app, contract) = proper_instantiate(true);
+
+        let stake_amount = ONE;
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &(ExecuteMsg::Stake { lock_duration: Some(90 * DAY) }),
+            &[Coin {
+                denom: INJEX_TOKEN.to_string(),
+                amount: Uint128::try_from(stake_amount).unwrap(),
+            }]
+        );
+
+        assert!(res.is_ok());
+
+        let block_info = app.block_info();
+
+        app.set_block(BlockInfo {
+            chain_id: block_info.chain_id,
+            height: block_info.height + 1,
+            time: block_info.time.plus_seconds(DAY),
+        });
+
+        // outside a pause the lock holds, or the boost could be claimed and the stake pulled
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &ExecuteMsg::EmergencyWithdraw {},
+            &[]
+        );
+
+        let lock_end = block_info.time.plus_seconds(90 * DAY).seconds();
+
+        expect_error(res, format!("Stake is locked until {}", lock_end));
+
+        let block_info = app.block_info();
+
+        app.set_block(BlockInfo {
+            chain_id: block_info.chain_id,
+            height: block_info.height + 1,
+            time: block_info.time.plus_seconds(90 * DAY),
+        });
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &ExecuteMsg::EmergencyWithdraw {},
+            &[]
+        );
+
+        assert!(res.is_ok());
+
+        let pending: Vec<PendingWithdrawal> = app
+            .wrap()
+            .query_wasm_smart(
+                contract.addr(),
+                &(QueryMsg::GetPendingWithdrawals { user: Addr::unchecked(USER) })
+            )
+            .unwrap();
+
+        assert_eq!(pending, vec![PendingWithdrawal {
+            amount: stake_amount,
+            release_time: app.block_info().time.plus_seconds(UNBONDING_PERIOD),
+        }]);
+
+        let state: State = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &QueryMsg::GetState {})
+            .unwrap();
+
+        assert_eq!(state.total_staked, Uint256::zero());
+        assert_eq!(state.total_unbonding, stake_amount);
+        assert_eq!(state.total_weighted_staked, Uint256::zero());
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &(ExecuteMsg::Claim {}),
+            &[]
+        );
+
+        expect_error(res, "No claims".to_string());
+    }
+
+    #[test]
+    fn two_step_admin_transfer() {
+        let (mut app, contract) = proper_instantiate(true);
+
+        let nominee = Addr::unchecked("inj12vpajtjf5cvmk2w737m0t8qwwkyjz0xgvxwyus");
+        let propose_msg = ExecuteMsg::ProposeAdmin {
+            address: nominee.to_string(),
+            expiry: None,
+        };
+
+        let res = app.execute_contract(nominee.clone(), contract.addr(), &propose_msg, &[]);
+
+        expect_error(res, format!("Missing role owner for {}", nominee));
+
+        let res = app.execute_contract(
+            nominee.clone(),
+            contract.addr(),
+            &(ExecuteMsg::AcceptAdmin {}),
+            &[]
+        );
+
+        expect_error(res, "No pending admin proposal".to_string());
+
+        let res = app.execute_contract(Addr::unchecked(USER), contract.addr(), &propose_msg, &[]);
+
+        assert!(res.is_ok());
+
+        let pending_admin: Option<PendingAdmin> = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &QueryMsg::GetPendingAdmin {})
+            .unwrap();
+
+        assert_eq!(
+            pending_admin,
+            Some(PendingAdmin {
+                address: nominee.clone(),
+                expiry: Expiration::Never {},
+            })
+        );
+
+        // the current admin keeps its role until the nominee accepts
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &(ExecuteMsg::AcceptAdmin {}),
+            &[]
+        );
+
+        expect_error(res, "Only pending admin".to_string());
+
+        let res = app.execute_contract(
+            nominee.clone(),
+            contract.addr(),
+            &(ExecuteMsg::AcceptAdmin {}),
+            &[]
+        );
+
+        assert!(res.is_ok());
+
+        let admin: Option<Addr> = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &QueryMsg::GetAdmin {})
+            .unwrap();
+        let pending_admin: Option<PendingAdmin> = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &QueryMsg::GetPendingAdmin {})
+            .unwrap();
+
+        assert_eq!(admin, Some(nominee.clone()));
+        assert_eq!(pending_admin, None);
+
+        let change_apr_msg = ExecuteMsg::ChangeApr {
+            denom: UncheckedDenom::Native(INJEX_TOKEN.to_string()),
+            new_apr: Decimal256::percent(10),
+        };
+
+        let res = app.execute_contract(Addr::unchecked(USER), contract.addr(), &change_apr_msg, &[]);
+
+        expect_error(res, format!("Missing role rate_manager for {}", USER));
+
+        let res = app.execute_contract(nominee, contract.addr(), &change_apr_msg, &[]);
+
+        assert!(res.is_ok());
+    }
+
+    #[test]
+    fn admin_proposal_expiry_and_cancel() {
+        let (mut app, contract) = proper_instantiate(true);
+
+        let nominee = Addr::unchecked("inj12vpajtjf5cvmk2w737m0t8qwwkyjz0xgvxwyus");
+        let expiry = app.block_info().time.plus_seconds(DAY);
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &(ExecuteMsg::ProposeAdmin {
+                address: nominee.to_string(),
+                expiry: Some(Expiration::AtTime(app.block_info().time)),
+            }),
+            &[]
+        );
+
+        expect_error(res, "Admin proposal expired".to_string());
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &(ExecuteMsg::ProposeAdmin {
+                address: nominee.to_string(),
+                expiry: Some(Expiration::AtTime(expiry)),
+            }),
+            &[]
+        );
+
+        assert!(res.is_ok());
+
+        let block_info = app.block_info();
+
+        app.set_block(BlockInfo {
+            chain_id: block_info.chain_id,
+            height: block_info.height + 1,
+            time: expiry,
+        });
+
+        let res = app.execute_contract(
+            nominee.clone(),
+            contract.addr(),
+            &(ExecuteMsg::AcceptAdmin {}),
+            &[]
+        );
+
+        expect_error(res, "Admin proposal expired".to_string());
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &(ExecuteMsg::CancelAdminProposal {}),
+            &[]
+        );
+
+        assert!(res.is_ok());
+
+        let pending_admin: Option<PendingAdmin> = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &QueryMsg::GetPendingAdmin {})
+            .unwrap();
+
+        assert_eq!(pending_admin, None);
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &(ExecuteMsg::CancelAdminProposal {}),
+            &[]
+        );
+
+        expect_error(res, "No pending admin proposal".to_string());
+    }
+
+    #[test]
+    fn renounce_admin() {
+        let (mut app, contract) = proper_instantiate(true);
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &(ExecuteMsg::RenounceAdmin {}),
+            &[]
+        );
+
+        assert!(res.is_ok());
+
+        let admin: Option<Addr> = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &QueryMsg::GetAdmin {})
+            .unwrap();
+
+        assert_eq!(admin, None);
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &(ExecuteMsg::Pause { reason: None }),
+            &[]
+        );
+
+        expect_error(res, format!("Missing role pauser for {}", USER));
+
+        // staking keeps working without an admin
+        stake_internal(&mut app, contract, ONE, true);
+    }
+
+    #[test]
+    fn grant_and_revoke_roles() {
+        let (mut app, contract) = proper_instantiate(true);
+
+        let manager = Addr::unchecked("inj12vpajtjf5cvmk2w737m0t8qwwkyjz0xgvxwyus");
+        let grant_msg = ExecuteMsg::GrantRole {
+            role: Role::RateManager,
+            address: manager.to_string(),
+        };
+        let change_apr_msg = ExecuteMsg::ChangeApr {
+            denom: UncheckedDenom::Native(INJEX_TOKEN.to_string()),
+            new_apr: Decimal256::percent(10),
+        };
+        let holders_msg = QueryMsg::GetRoleHolders { role: Role::RateManager };
+
+        let res = app.execute_contract(manager.clone(), contract.addr(), &grant_msg, &[]);
+
+        expect_error(res, format!("Missing role owner for {}", manager));
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &(ExecuteMsg::GrantRole {
+                role: Role::Owner,
+                address: manager.to_string(),
+            }),
+            &[]
+        );
+
+        expect_error(res, "Owner can only be transferred through ProposeAdmin".to_string());
+
+        let res = app.execute_contract(Addr::unchecked(USER), contract.addr(), &grant_msg, &[]);
+
+        assert!(res.is_ok());
+
+        let holders: Vec<Addr> = app.wrap().query_wasm_smart(contract.addr(), &holders_msg).unwrap();
+        let owners: Vec<Addr> = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &(QueryMsg::GetRoleHolders { role: Role::Owner }))
+            .unwrap();
+
+        assert_eq!(holders, vec![manager.clone()]);
+        assert_eq!(owners, vec![Addr::unchecked(USER)]);
+
+        let res = app.execute_contract(manager.clone(), contract.addr(), &change_apr_msg, &[]);
+
+        assert!(res.is_ok());
+        assert_eq!(query_apr(&app, &contract), Decimal256::percent(10));
+
+        // a role only unlocks its own messages
+        let res = app.execute_contract(
+            manager.clone(),
+            contract.addr(),
+            &(ExecuteMsg::Pause { reason: None }),
+            &[]
+        );
+
+        expect_error(res, format!("Missing role pauser for {}", manager));
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &(ExecuteMsg::RevokeRole {
+                role: Role::RateManager,
+                address: manager.to_string(),
+            }),
+            &[]
+        );
+
+        assert!(res.is_ok());
+
+        let holders: Vec<Addr> = app.wrap().query_wasm_smart(contract.addr(), &holders_msg).unwrap();
+
+        assert!(holders.is_empty());
+
+        let res = app.execute_contract(manager.clone(), contract.addr(), &change_apr_msg, &[]);
+
+        expect_error(res, format!("Missing role rate_manager for {}", manager));
+    }
+
+    #[test]
+    fn claim_requires_backing_for_all_stakers() {
+        let (mut app, contract) = proper_instantiate_with_reward(USDT, false);
+
+        let stake_amount = ONE;
+        let other = Addr::unchecked("inj12vpajtjf5cvmk2w737m0t8qwwkyjz0xgvxwyus");
+        let solvency_msg = QueryMsg::GetSolvency {};
+
+        app.send_tokens(Addr::unchecked(USER), other.clone(), &[
+            Coin {
+                denom: INJEX_TOKEN.to_string(),
+                amount: Uint128::try_from(stake_amount).unwrap(),
+            },
+        ]).unwrap();
+
+        stake_internal(&mut app, contract.clone(), stake_amount, true);
+
+        let res = app.execute_contract(
+            other.clone(),
+            contract.addr(),
+            &(ExecuteMsg::Stake { lock_duration: None }),
+            &[Coin {
+                denom: INJEX_TOKEN.to_string(),
+                amount: Uint128::try_from(stake_amount).unwrap(),
+            }]
+        );
+
+        assert!(res.is_ok());
+
+        let block_info = app.block_info();
+
+        app.set_block(BlockInfo {
+            chain_id: block_info.chain_id,
+            height: block_info.height + 1,
+            time: block_info.time.plus_seconds(31_536_000),
+        });
+
+        let reward = calculate_reward(
+            stake_amount,
+            calculate_ci(ONE, APR, 31_536_000_u128.into()),
+            ONE
+        );
+
+        // enough for one staker only
+        app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &ExecuteMsg::FundRewards {},
+            &[
+                Coin {
+                    denom: USDT.to_string(),
+                    amount: Uint128::try_from(reward).unwrap(),
+                },
+            ]
+        ).unwrap();
+
+        let solvency: Vec<SolvencyResponse> = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &solvency_msg)
+            .unwrap();
+
+        assert_eq!(solvency, vec![SolvencyResponse {
+            denom: Denom::Native(USDT.to_string()),
+            balance: reward,
+            principal: Uint256::zero(),
+            reward_reserve: reward,
+            liability: reward + reward,
+            surplus: Uint256::zero(),
+            deficit: reward,
+        }]);
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &ExecuteMsg::Claim {},
+            &[]
+        );
+
+        expect_error(
+            res,
+            concat!(
+                "Insufficient contract balance: USDT reserve 200000000000000000 ",
+                "is below liability 400000000000000000"
+            ).to_string()
+        );
+
+        app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &ExecuteMsg::FundRewards {},
+            &[
+                Coin {
+                    denom: USDT.to_string(),
+                    amount: Uint128::try_from(reward).unwrap(),
+                },
+            ]
+        ).unwrap();
+
+        for staker in [Addr::unchecked(USER), other] {
+            let res = app.execute_contract(staker, contract.addr(), &ExecuteMsg::Claim {}, &[]);
+
+            assert!(res.is_ok());
+        }
+
+        let solvency: Vec<SolvencyResponse> = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &solvency_msg)
+            .unwrap();
+
+        assert_eq!(solvency[0].balance, Uint256::zero());
+        assert_eq!(solvency[0].reward_reserve, Uint256::zero());
+        assert_eq!(solvency[0].liability, Uint256::zero());
+        assert_eq!(solvency[0].deficit, Uint256::zero());
+    }
+
+    #[test]
+    fn fund_and_withdraw_excess_rewards() {
+        let (mut app, contract) = proper_instantiate(false);
+
+        let stake_amount = ONE;
+        let fund_amount = Uint256::from_u128(10_000000000000000000_u128);
+        let treasurer = Addr::unchecked("inj12vpajtjf5cvmk2w737m0t8qwwkyjz0xgvxwyus");
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &ExecuteMsg::FundRewards {},
+            &[
+                Coin {
+                    denom: USDT.to_string(),
+                    amount: Uint128::new(1_000_000),
+                },
+            ]
+        );
+
+        expect_error(res, "Invalid coin USDT passed in funds".to_string());
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &ExecuteMsg::FundRewards {},
+            &[
+                Coin {
+                    denom: INJEX_TOKEN.to_string(),
+                    amount: Uint128::try_from(fund_amount).unwrap(),
+                },
+            ]
+        );
+
+        assert!(res.is_ok());
+
+        let funded: Vec<RewardAmount> = app
+            .wrap()
+            .query_wasm_smart(
+                contract.addr(),
+                &(QueryMsg::GetFundedRewards { user: Addr::unchecked(USER) })
+            )
+            .unwrap();
+
+        assert_eq!(funded, vec![RewardAmount {
+            denom: Denom::Native(INJEX_TOKEN.to_string()),
+            amount: fund_amount,
+        }]);
+
+        stake_internal(&mut app, contract.clone(), stake_amount, true);
+
+        let block_info = app.block_info();
+
+        app.set_block(BlockInfo {
+            chain_id: block_info.chain_id,
+            height: block_info.height + 1,
+            time: block_info.time.plus_seconds(31_536_000),
+        });
+
+        let liability = calculate_reward(
+            stake_amount,
+            calculate_ci(ONE, APR, 31_536_000_u128.into()),
+            ONE
+        );
+        let withdraw_msg = |amount: Uint256| ExecuteMsg::WithdrawExcessRewards {
+            denom: UncheckedDenom::Native(INJEX_TOKEN.to_string()),
+            amount,
+        };
+
+        let res = app.execute_contract(
+            treasurer.clone(),
+            contract.addr(),
+            &withdraw_msg(Uint256::one()),
+            &[]
+        );
+
+        expect_error(res, format!("Missing role treasurer for {}", treasurer));
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &(ExecuteMsg::GrantRole {
+                role: Role::Treasurer,
+                address: treasurer.to_string(),
+            }),
+            &[]
+        );
+
+        assert!(res.is_ok());
+
+        // the reward accrued by the staker stays in the reserve
+        let res = app.execute_contract(
+            treasurer.clone(),
+            contract.addr(),
+            &withdraw_msg(fund_amount),
+            &[]
+        );
+
+        expect_error(res, "Amount exceeds rewards not owed to stakers".to_string());
+
+        let res = app.execute_contract(
+            treasurer.clone(),
+            contract.addr(),
+            &withdraw_msg(fund_amount - liability),
+            &[]
+        );
+
+        assert!(res.is_ok());
+
+        let treasurer_balance = app
+            .wrap()
+            .query_balance(treasurer.to_string(), INJEX_TOKEN.to_string())
+            .unwrap();
+
+        assert_eq!(Uint256::from_uint128(treasurer_balance.amount), fund_amount - liability);
+
+        let solvency: Vec<SolvencyResponse> = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &QueryMsg::GetSolvency {})
+            .unwrap();
+
+        assert_eq!(solvency[0].reward_reserve, liability);
+        assert_eq!(solvency[0].surplus, Uint256::zero());
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &ExecuteMsg::Claim {},
+            &[]
+        );
+
+        assert!(res.is_ok());
+    }
+
+    #[test]
+    fn scheduled_apr_changes() {
+        let (mut app, contract) = proper_instantiate(true);
+
+        let stake_amount = ONE;
+        let start = app.block_info().time;
+        let denom = UncheckedDenom::Native(INJEX_TOKEN.to_string());
+        let schedule_msg = |effective_time: Timestamp, apr: u64| ExecuteMsg::ScheduleAprChange {
+            denom: denom.clone(),
+            effective_time,
+            apr: Decimal256::bps(apr),
+        };
+        let schedule_query = QueryMsg::GetAprSchedule {
+            denom: UncheckedDenom::Native(INJEX_TOKEN.to_string()),
+        };
+
+        stake_internal(&mut app, contract.clone(), stake_amount, true);
+
+        let res = app.execute_contract(
+            Addr::unchecked("inj12vpajtjf5cvmk2w737m0t8qwwkyjz0xgvxwyus"),
+            contract.addr(),
+            &schedule_msg(start.plus_seconds(100), 4_000),
+            &[]
+        );
+
+        expect_error(
+            res,
+            "Missing role rate_manager for inj12vpajtjf5cvmk2w737m0t8qwwkyjz0xgvxwyus".to_string()
+        );
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &schedule_msg(start, 4_000),
+            &[]
+        );
+
+        expect_error(res, "Effective time must be in the future".to_string());
+
+        // scheduled out of order on purpose, the schedule is kept sorted
+        for (offset, apr) in [(300, 1_000), (100, 4_000), (400, 3_000)] {
+            let res = app.execute_contract(
+                Addr::unchecked(USER),
+                contract.addr(),
+                &schedule_msg(start.plus_seconds(offset), apr),
+                &[]
+            );
+
+            assert!(res.is_ok());
+        }
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &schedule_msg(start.plus_seconds(100), 5_000),
+            &[]
+        );
+
+        expect_error(res, "APR change already scheduled at that time".to_string());
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &(ExecuteMsg::CancelAprChange {
+                denom: denom.clone(),
+                effective_time: start.plus_seconds(400),
+            }),
+            &[]
+        );
+
+        assert!(res.is_ok());
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &(ExecuteMsg::CancelAprChange {
+                denom: denom.clone(),
+                effective_time: start.plus_seconds(400),
+            }),
+            &[]
+        );
+
+        expect_error(res, "APR change not found".to_string());
+
+        let schedule: Vec<AprChange> = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &schedule_query)
+            .unwrap();
+
+        assert_eq!(schedule, vec![
+            AprChange {
+                effective_time: start.plus_seconds(100),
+                apr: Decimal256::percent(40),
+            },
+            AprChange {
+                effective_time: start.plus_seconds(300),
+                apr: Decimal256::percent(10),
+            }
+        ]);
+
+        // CW20 denoms are validated like in every execute message
+        let res: StdResult<Vec<AprChange>> = app
+            .wrap()
+            .query_wasm_smart(
+                contract.addr(),
+                &(QueryMsg::GetAprSchedule { denom: UncheckedDenom::Cw20("INVALID".to_string()) })
+            );
+
+        assert!(res.unwrap_err().to_string().contains("not normalized"));
+
+        let block_info = app.block_info();
+
+        // nobody interacts at either switch time
+        app.set_block(BlockInfo {
+            chain_id: block_info.chain_id,
+            height: block_info.height + 1,
+            time: start.plus_seconds(500),
+        });
+
+        let ci = calculate_ci(ONE, APR, 100_u128.into());
+        let ci = calculate_ci(ci, Decimal256::percent(40), 200_u128.into());
+        let ci = calculate_ci(ci, Decimal256::percent(10), 200_u128.into());
+
+        let claimable: Vec<RewardAmount> = app
+            .wrap()
+            .query_wasm_smart(
+                contract.addr(),
+                &(QueryMsg::GetClaimableAmount { user: Addr::unchecked(USER) })
+            )
+            .unwrap();
+
+        assert_eq!(claimable[0].amount, calculate_reward(stake_amount, ci, ONE));
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &ExecuteMsg::Claim {},
+            &[]
+        );
+
+        assert!(res.is_ok());
+
+        let state: State = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &QueryMsg::GetState {})
+            .unwrap();
+
+        assert_eq!(state.reward_streams[0].ci_current, ci);
+        assert_eq!(state.reward_streams[0].apr, Decimal256::percent(10));
+        assert!(state.reward_streams[0].apr_schedule.is_empty());
+    }
+
+    #[test]
+    fn rewards_window_clamps_accrual() {
+        let start = mock_env().block.time;
+        let mut msg = instantiate_msg(INJEX_TOKEN);
+
+        msg.rewards_start = Some(start.plus_seconds(100));
+        msg.rewards_end = Some(start.plus_seconds(400));
+
+        let (mut app, contract) = proper_instantiate_with_msg(msg, INJEX_TOKEN, true);
+
+        let stake_amount = ONE;
+        let claimable_msg = QueryMsg::GetClaimableAmount { user: Addr::unchecked(USER) };
+
+        stake_internal(&mut app, contract.clone(), stake_amount, true);
+
+        let block_info = app.block_info();
+
+        app.set_block(BlockInfo {
+            chain_id: block_info.chain_id,
+            height: block_info.height + 1,
+            time: start.plus_seconds(600),
+        });
+
+        // only the 300 seconds between start and end accrue
+        let ci = calculate_ci(ONE, APR, 300_u128.into());
+
+        let claimable: Vec<RewardAmount> = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &claimable_msg)
+            .unwrap();
+
+        assert_eq!(claimable[0].amount, calculate_reward(stake_amount, ci, ONE));
+
+        let res = app.execute_contract(
+            Addr::unchecked("inj12vpajtjf5cvmk2w737m0t8qwwkyjz0xgvxwyus"),
+            contract.addr(),
+            &(ExecuteMsg::ExtendRewardsEnd { rewards_end: start.plus_seconds(1_000) }),
+            &[]
+        );
+
+        expect_error(
+            res,
+            "Missing role owner for inj12vpajtjf5cvmk2w737m0t8qwwkyjz0xgvxwyus".to_string()
+        );
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &(ExecuteMsg::ExtendRewardsEnd { rewards_end: start.plus_seconds(500) }),
+            &[]
+        );
+
+        expect_error(res, "Invalid reward program window".to_string());
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &(ExecuteMsg::ExtendRewardsEnd { rewards_end: start.plus_seconds(1_000) }),
+            &[]
+        );
+
+        assert!(res.is_ok());
+
+        let config: Config = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &QueryMsg::GetConfig {})
+            .unwrap();
+
+        assert_eq!(config.rewards_end, Some(start.plus_seconds(1_000)));
+
+        let block_info = app.block_info();
+
+        app.set_block(BlockInfo {
+            chain_id: block_info.chain_id,
+            height: block_info.height + 1,
+            time: start.plus_seconds(800),
+        });
+
+        // the gap between the old end and the extension does not accrue retroactively
+        let ci = calculate_ci(ci, APR, 200_u128.into());
+
+        let claimable: Vec<RewardAmount> = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &claimable_msg)
+            .unwrap();
+
+        assert_eq!(claimable[0].amount, calculate_reward(stake_amount, ci, ONE));
+    }
+
+    #[test]
+    fn set_rewards_end_on_open_program() {
+        let (mut app, contract) = proper_instantiate(true);
+
+        let stake_amount = ONE;
+        let start = app.block_info().time;
+
+        stake_internal(&mut app, contract.clone(), stake_amount, true);
+
+        let block_info = app.block_info();
+
+        app.set_block(BlockInfo {
+            chain_id: block_info.chain_id,
+            height: block_info.height + 1,
+            time: start.plus_seconds(100),
+        });
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &(ExecuteMsg::ExtendRewardsEnd { rewards_end: start.plus_seconds(50) }),
+            &[]
+        );
+
+        expect_error(res, "Invalid reward program window".to_string());
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &(ExecuteMsg::ExtendRewardsEnd { rewards_end: start.plus_seconds(300) }),
+            &[]
+        );
+
+        assert!(res.is_ok());
+
+        let config: Config = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &QueryMsg::GetConfig {})
+            .unwrap();
+
+        assert_eq!(config.rewards_end, Some(start.plus_seconds(300)));
+
+        let block_info = app.block_info();
+
+        app.set_block(BlockInfo {
+            chain_id: block_info.chain_id,
+            height: block_info.height + 1,
+            time: start.plus_seconds(1_000),
+        });
+
+        // accrual stops at the new end
+        let ci = calculate_ci(ONE, APR, 300_u128.into());
+
+        let claimable: Vec<RewardAmount> = app
+            .wrap()
+            .query_wasm_smart(
+                contract.addr(),
+                &(QueryMsg::GetClaimableAmount { user: Addr::unchecked(USER) })
+            )
+            .unwrap();
+
+        assert_eq!(claimable[0].amount, calculate_reward(stake_amount, ci, ONE));
+    }
+
+    #[test]
+    fn stake_before_rewards_start() {
+        let start = mock_env().block.time;
+        let mut msg = instantiate_msg(INJEX_TOKEN);
+
+        msg.rewards_start = Some(start.plus_seconds(100));
+        msg.rewards_end = Some(start.plus_seconds(100));
+
+        let mut app = mock_app();
+        let code_id = app.store_code(contract_template());
+
+        let res = app
+            .instantiate_contract(code_id, Addr::unchecked(ADMIN), &msg, &[], "test", None)
+            .map(|_| AppResponse::default());
+
+        expect_error(res, "Invalid reward program window".to_string());
+
+        msg.rewards_end = None;
+        msg.stake_requires_start = true;
+
+        let (mut app, contract) = proper_instantiate_with_msg(msg, INJEX_TOKEN, true);
+
+        let stake_msg = ExecuteMsg::Stake { lock_duration: None };
+        let funds = [
+            Coin {
+                denom: INJEX_TOKEN.to_string(),
+                amount: Uint128::new(1_000_000),
+            },
+        ];
+
+        let res = app.execute_contract(Addr::unchecked(USER), contract.addr(), &stake_msg, &funds);
+
+        expect_error(res, "Reward program has not started".to_string());
+
+        let block_info = app.block_info();
+
+        app.set_block(BlockInfo {
+            chain_id: block_info.chain_id,
+            height: block_info.height + 1,
+            time: start.plus_seconds(100),
+        });
+
+        let res = app.execute_contract(Addr::unchecked(USER), contract.addr(), &stake_msg, &funds);
+
+        assert!(res.is_ok());
+    }
+
+    #[test]
+    fn emission_mode_splits_rate_pro_rata() {
+        let mut msg = instantiate_msg(USDT);
+
+        msg.reward_mode = RewardMode::Emission;
+        msg.reward_streams[0].apr = Decimal256::zero();
+        msg.reward_streams[0].emission_rate = Uint256::from_u128(1_000_000_u128);
+
+        let (mut app, contract) = proper_instantiate_with_msg(msg, USDT, true);
+
+        let start = app.block_info().time;
+        let other = Addr::unchecked("inj12vpajtjf5cvmk2w737m0t8qwwkyjz0xgvxwyus");
+        let usdt = UncheckedDenom::Native(USDT.to_string());
+
+        let reward_mode: RewardMode = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &QueryMsg::GetRewardMode {})
+            .unwrap();
+
+        assert_eq!(reward_mode, RewardMode::Emission);
+
+        app.send_tokens(Addr::unchecked(USER), other.clone(), &[
+            Coin {
+                denom: INJEX_TOKEN.to_string(),
+                amount: Uint128::new(3_000000000000000000),
+            },
+        ]).unwrap();
+
+        stake_internal(&mut app, contract.clone(), ONE, false);
+
+        let res = app.execute_contract(
+            other.clone(),
+            contract.addr(),
+            &(ExecuteMsg::Stake { lock_duration: None }),
+            &[Coin {
+                denom: INJEX_TOKEN.to_string(),
+                amount: Uint128::new(3_000000000000000000),
+            }]
+        );
+
+        assert!(res.is_ok());
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &(ExecuteMsg::ChangeApr {
+                denom: usdt.clone(),
+                new_apr: APR,
+            }),
+            &[]
+        );
+
+        expect_error(res, "Not supported in the current reward mode".to_string());
+
+        let block_info = app.block_info();
+
+        app.set_block(BlockInfo {
+            chain_id: block_info.chain_id,
+            height: block_info.height + 1,
+            time: start.plus_seconds(100),
+        });
+
+        let claimable = |app: &App, user: &Addr| -> Uint256 {
+            let claimable: Vec<RewardAmount> = app
+                .wrap()
+                .query_wasm_smart(
+                    contract.addr(),
+                    &(QueryMsg::GetClaimableAmount { user: user.clone() })
+                )
+                .unwrap();
+
+            claimable[0].amount
+        };
+
+        // 100 seconds at 1_000_000 a second, split 1:3 regardless of TVL
+        assert_eq!(claimable(&app, &Addr::unchecked(USER)), Uint256::from_u128(25_000_000_u128));
+        assert_eq!(claimable(&app, &other), Uint256::from_u128(75_000_000_u128));
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &(ExecuteMsg::SetEmissionBudget {
+                denom: usdt,
+                amount: Uint256::from_u128(1_000_000_000_u128),
+                duration: 100,
+            }),
+            &[]
+        );
+
+        assert!(res.is_ok());
+
+        let block_info = app.block_info();
+
+        // emission stops once the budget is spent
+        app.set_block(BlockInfo {
+            chain_id: block_info.chain_id,
+            height: block_info.height + 1,
+            time: start.plus_seconds(300),
+        });
+
+        assert_eq!(claimable(&app, &Addr::unchecked(USER)), Uint256::from_u128(275_000_000_u128));
+        assert_eq!(claimable(&app, &other), Uint256::from_u128(825_000_000_u128));
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &ExecuteMsg::Claim {},
+            &[]
+        );
+
+        assert!(res.is_ok());
+    }
+
+    #[test]
+    fn staking_caps() {
+        let mut msg = instantiate_msg(INJEX_TOKEN);
+
+        msg.max_total_staked = Some(Uint256::from_u128(1_500_u128));
+        msg.min_stake_amount = Some(Uint256::from_u128(100_u128));
+        msg.max_stake_per_user = Some(Uint256::from_u128(1_000_u128));
+
+        let (mut app, contract) = proper_instantiate_with_msg(msg, INJEX_TOKEN, true);
+
+        let other = Addr::unchecked("inj12vpajtjf5cvmk2w737m0t8qwwkyjz0xgvxwyus");
+        let stake = |app: &mut App, sender: &Addr, amount: u128| {
+            app.execute_contract(
+                sender.clone(),
+                contract.addr(),
+                &(ExecuteMsg::Stake { lock_duration: None }),
+                &[Coin {
+                    denom: INJEX_TOKEN.to_string(),
+                    amount: Uint128::new(amount),
+                }]
+            )
+        };
+
+        app.send_tokens(Addr::unchecked(USER), other.clone(), &[
+            Coin {
+                denom: INJEX_TOKEN.to_string(),
+                amount: Uint128::new(1_000),
+            },
+        ]).unwrap();
+
+        let res = stake(&mut app, &Addr::unchecked(USER), 50);
+
+        expect_error(res, "Stake 50 is below minimum 100".to_string());
+
+        let res = stake(&mut app, &Addr::unchecked(USER), 100);
+
+        assert!(res.is_ok());
+
+        let res = stake(&mut app, &Addr::unchecked(USER), 1_000);
+
+        expect_error(res, "Stake 1100 exceeds per-user cap 1000".to_string());
+
+        let capacity: RemainingCapacityResponse = app
+            .wrap()
+            .query_wasm_smart(
+                contract.addr(),
+                &(QueryMsg::GetRemainingCapacity { user: Some(Addr::unchecked(USER)) })
+            )
+            .unwrap();
+
+        assert_eq!(capacity, RemainingCapacityResponse {
+            total: Some(Uint256::from_u128(1_400_u128)),
+            user: Some(Uint256::from_u128(900_u128)),
+        });
+
+        let res = stake(&mut app, &other, 1_000);
+
+        assert!(res.is_ok());
+
+        let res = stake(&mut app, &Addr::unchecked(USER), 500);
+
+        expect_error(res, "Total stake 1600 exceeds cap 1500".to_string());
+
+        // leftover dust below the minimum is rejected, closing the position is not
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &(ExecuteMsg::Unstake { amount: Uint256::from_u128(50_u128) }),
+            &[]
+        );
+
+        expect_error(res, "Stake 50 is below minimum 100".to_string());
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &(ExecuteMsg::Unstake { amount: Uint256::from_u128(100_u128) }),
+            &[]
+        );
+
+        assert!(res.is_ok());
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &(ExecuteMsg::UpdateStakingCaps {
+                max_total_staked: None,
+                min_stake_amount: Some(Uint256::from_u128(100_u128)),
+                max_stake_per_user: Some(Uint256::from_u128(10_u128)),
+            }),
+            &[]
+        );
+
+        expect_error(res, "Invalid staking caps".to_string());
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &(ExecuteMsg::UpdateStakingCaps {
+                max_total_staked: None,
+                min_stake_amount: None,
+                max_stake_per_user: None,
+            }),
+            &[]
+        );
+
+        assert!(res.is_ok());
+
+        let capacity: RemainingCapacityResponse = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &(QueryMsg::GetRemainingCapacity { user: None }))
+            .unwrap();
+
+        assert_eq!(capacity, RemainingCapacityResponse { total: None, user: None });
+
+        let res = stake(&mut app, &Addr::unchecked(USER), 5_000);
+
+        assert!(res.is_ok());
+    }
+
+    #[test]
+    fn compounded_rewards_skip_caps() {
+        let mut msg = instantiate_msg(INJEX_TOKEN);
+
+        msg.max_total_staked = Some(Uint256::from_u128(1_000_u128));
+        msg.max_stake_per_user = Some(Uint256::from_u128(1_000_u128));
+
+        let (mut app, contract) = proper_instantiate_with_msg(msg, INJEX_TOKEN, true);
+
+        let user = Addr::unchecked(USER);
+        let stake = |app: &mut App, amount: u128| {
+            app.execute_contract(
+                user.clone(),
+                contract.addr(),
+                &(ExecuteMsg::Stake { lock_duration: None }),
+                &[Coin {
+                    denom: INJEX_TOKEN.to_string(),
+                    amount: Uint128::new(amount),
+                }]
+            )
+        };
+
+        stake(&mut app, 900).unwrap();
+        app.execute_contract(
+            user.clone(),
+            contract.addr(),
+            &(ExecuteMsg::SetAutoCompound { enabled: true }),
+            &[]
+        ).unwrap();
+
+        let block_info = app.block_info();
+
+        app.set_block(BlockInfo {
+            chain_id: block_info.chain_id,
+            height: block_info.height + 1,
+            time: block_info.time.plus_seconds(365 * DAY),
+        });
+
+        // the deposit fits, the rewards compounded along with it are not capped
+        stake(&mut app, 100).unwrap();
+
+        let staker_info: StakerInfo = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &(QueryMsg::GetStakerInfo { user: user.clone() }))
+            .unwrap();
+
+        assert!(staker_info.staked > Uint256::from_u128(1_000_u128));
+
+        let res = stake(&mut app, 1);
+
+        expect_error(
+            res,
+            format!(
+                "Total stake {} exceeds cap 1000",
+                staker_info.staked + Uint256::from_u128(1_u128)
+            )
+        );
+    }
+
+    #[test]
+    fn list_stakers_and_count() {
+        let (mut app, contract) = proper_instantiate(true);
+
+        let other = Addr::unchecked("inj12vpajtjf5cvmk2w737m0t8qwwkyjz0xgvxwyus");
+        let stake_msg = ExecuteMsg::Stake { lock_duration: None };
+        let funds = vec![Coin {
+            denom: INJEX_TOKEN.to_string(),
+            amount: Uint128::try_from(ONE).unwrap(),
+        }];
+
+        app.send_tokens(Addr::unchecked(USER), other.clone(), &funds).unwrap();
+
+        app.execute_contract(Addr::unchecked(USER), contract.addr(), &stake_msg, &funds).unwrap();
+        app.execute_contract(other.clone(), contract.addr(), &stake_msg, &funds).unwrap();
+
+        let count: u64 = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &(QueryMsg::GetStakerCount {}))
+            .unwrap();
+
+        assert_eq!(count, 2);
+
+        let block_info = app.block_info();
+
+        app.set_block(BlockInfo {
+            chain_id: block_info.chain_id,
+            height: block_info.height + 1,
+            time: block_info.time.plus_seconds(31_536_000),
+        });
+
+        let page: Vec<StakerSummary> = app
+            .wrap()
+            .query_wasm_smart(
+                contract.addr(),
+                &(QueryMsg::ListStakers { start_after: None, limit: Some(1) })
+            )
+            .unwrap();
+
+        // a year at 20% on a single stake
+        assert_eq!(page, vec![StakerSummary {
+            address: other.clone(),
+            staked: ONE,
+            rewards: vec![RewardAmount {
+                denom: Denom::Native(INJEX_TOKEN.to_string()),
+                amount: Uint256::zero(),
+            }],
+            claimable: vec![RewardAmount {
+                denom: Denom::Native(INJEX_TOKEN.to_string()),
+                amount: ONE / Uint256::from_u128(5_u128),
+            }],
+        }]);
+
+        let page: Vec<StakerSummary> = app
+            .wrap()
+            .query_wasm_smart(
+                contract.addr(),
+                &(QueryMsg::ListStakers { start_after: Some(other.clone()), limit: None })
+            )
+            .unwrap();
+
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].address, Addr::unchecked(USER));
+
+        let res = app.execute_contract(
+            other.clone(),
+            contract.addr(),
+            &(ExecuteMsg::Unstake { amount: ONE }),
+            &[]
+        );
+
+        assert!(res.is_ok());
+
+        let count: u64 = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &(QueryMsg::GetStakerCount {}))
+            .unwrap();
+
+        assert_eq!(count, 1);
+
+        // the closed position is still listed while it holds rewards
+        let page: Vec<StakerSummary> = app
+            .wrap()
+            .query_wasm_smart(
+                contract.addr(),
+                &(QueryMsg::ListStakers { start_after: None, limit: None })
+            )
+            .unwrap();
+
+        assert_eq!(page.len(), 2);
+        assert_eq!(page[0].staked, Uint256::zero());
+        assert_eq!(page[0].rewards, page[0].claimable);
+    }
+
+    #[test]
+    fn staked_balance_snapshots() {
+        let (mut app, contract) = proper_instantiate(true);
+
+        let other = Addr::unchecked("inj12vpajtjf5cvmk2w737m0t8qwwkyjz0xgvxwyus");
+        let stake_msg = ExecuteMsg::Stake { lock_duration: None };
+        let funds = vec![Coin {
+            denom: INJEX_TOKEN.to_string(),
+            amount: Uint128::try_from(ONE).unwrap(),
+        }];
+
+        app.send_tokens(Addr::unchecked(USER), other.clone(), &funds).unwrap();
+
+        let balance_at = |app: &App, user: &Addr, height: Option<u64>| -> Uint256 {
+            let res: StakedBalanceAtHeightResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    contract.addr(),
+                    &(QueryMsg::StakedBalanceAtHeight { user: user.clone(), height })
+                )
+                .unwrap();
+
+            res.balance
+        };
+        let total_at = |app: &App, height: Option<u64>| -> Uint256 {
+            let res: TotalStakedAtHeightResponse = app
+                .wrap()
+                .query_wasm_smart(contract.addr(), &(QueryMsg::TotalStakedAtHeight { height }))
+                .unwrap();
+
+            res.total
+        };
+        let next_block = |app: &mut App| {
+            let block_info = app.block_info();
+
+            app.set_block(BlockInfo {
+                chain_id: block_info.chain_id,
+                height: block_info.height + 1,
+                time: block_info.time.plus_seconds(DAY),
+            });
+        };
+
+        let user = Addr::unchecked(USER);
+        let h0 = app.block_info().height;
+
+        app.execute_contract(user.clone(), contract.addr(), &stake_msg, &funds).unwrap();
+
+        // a stake counts from the next block, not in the block it was made
+        assert_eq!(balance_at(&app, &user, Some(h0)), Uint256::zero());
+        assert_eq!(total_at(&app, Some(h0)), Uint256::zero());
+        assert_eq!(balance_at(&app, &user, Some(h0 + 1)), ONE);
+        assert_eq!(total_at(&app, Some(h0 + 1)), ONE);
+
+        next_block(&mut app);
+
+        let h1 = app.block_info().height;
+
+        app.execute_contract(other.clone(), contract.addr(), &stake_msg, &funds).unwrap();
+        app.execute_contract(
+            user.clone(),
+            contract.addr(),
+            &(ExecuteMsg::Unstake { amount: ONE / Uint256::from_u128(2_u128) }),
+            &[]
+        ).unwrap();
+
+        next_block(&mut app);
+
+        let h2 = app.block_info().height;
+
+        app.execute_contract(
+            other.clone(),
+            contract.addr(),
+            &ExecuteMsg::EmergencyWithdraw {},
+            &[]
+        ).unwrap();
+
+        next_block(&mut app);
+
+        // earlier heights keep the balances they had, whatever happened since
+        assert_eq!(balance_at(&app, &user, Some(h1)), ONE);
+        assert_eq!(balance_at(&app, &other, Some(h1)), Uint256::zero());
+        assert_eq!(total_at(&app, Some(h1)), ONE);
+
+        assert_eq!(balance_at(&app, &user, Some(h2)), ONE / Uint256::from_u128(2_u128));
+        assert_eq!(balance_at(&app, &other, Some(h2)), ONE);
+        assert_eq!(total_at(&app, Some(h2)), ONE + ONE / Uint256::from_u128(2_u128));
+
+        assert_eq!(balance_at(&app, &user, None), ONE / Uint256::from_u128(2_u128));
+        assert_eq!(balance_at(&app, &other, None), Uint256::zero());
+        assert_eq!(total_at(&app, None), ONE / Uint256::from_u128(2_u128));
+
+        let res: StakedBalanceAtHeightResponse = app
+            .wrap()
+            .query_wasm_smart(
+                contract.addr(),
+                &(QueryMsg::StakedBalanceAtHeight { user: user.clone(), height: None })
+            )
+            .unwrap();
+
+        assert_eq!(res.height, app.block_info().height);
+    }
+
+    #[test]
+    fn stake_change_hooks() {
+        let (mut app, contract) = proper_instantiate(true);
+
+        let receiver_id = app.store_code(hook_receiver_template());
+        let receiver = app
+            .instantiate_contract(
+                receiver_id,
+                Addr::unchecked(ADMIN),
+                &(MockHookReceiverInstantiateMsg { fail: false }),
+                &[],
+                "receiver",
+                None
+            )
+            .unwrap();
+        let failing_receiver = app
+            .instantiate_contract(
+                receiver_id,
+                Addr::unchecked(ADMIN),
+                &(MockHookReceiverInstantiateMsg { fail: true }),
+                &[],
+                "failing receiver",
+                None
+            )
+            .unwrap();
+
+        let fake_user = "inj12vpajtjf5cvmk2w737m0t8qwwkyjz0xgvxwyus";
+        let add_hook = ExecuteMsg::AddHook { address: receiver.to_string() };
+
+        let res = app.execute_contract(Addr::unchecked(fake_user), contract.addr(), &add_hook, &[]);
+
+        expect_error(res, format!("Missing role owner for {}", fake_user));
+
+        app.execute_contract(Addr::unchecked(ADMIN), contract.addr(), &add_hook, &[]).unwrap();
+
+        let res = app.execute_contract(Addr::unchecked(ADMIN), contract.addr(), &add_hook, &[]);
+
+        expect_error(res, format!("Hook {} is already registered", receiver));
+
+        let user = Addr::unchecked(USER);
+        let half = ONE / Uint256::from_u128(2_u128);
+        let funds = vec![Coin {
+            denom: INJEX_TOKEN.to_string(),
+            amount: Uint128::try_from(ONE).unwrap(),
+        }];
+
+        app.execute_contract(
+            user.clone(),
+            contract.addr(),
+            &(ExecuteMsg::Stake { lock_duration: None }),
+            &funds
+        ).unwrap();
+        app.execute_contract(
+            user.clone(),
+            contract.addr(),
+            &(ExecuteMsg::Unstake { amount: half }),
+            &[]
+        ).unwrap();
+
+        let block_info = app.block_info();
+
+        app.set_block(BlockInfo {
+            chain_id: block_info.chain_id,
+            height: block_info.height + 1,
+            time: block_info.time.plus_seconds(DAY),
+        });
+
+        app.execute_contract(user.clone(), contract.addr(), &(ExecuteMsg::Claim {}), &[]).unwrap();
+
+        let received: Vec<StakeChangedHookMsg> = app
+            .wrap()
+            .query_wasm_smart(receiver.clone(), &Empty {})
+            .unwrap();
+
+        assert_eq!(received, vec![
+            StakeChangedHookMsg::StakeChangedHook {
+                addr: user.clone(),
+                old_staked: Uint256::zero(),
+                new_staked: ONE,
+            },
+            StakeChangedHookMsg::StakeChangedHook {
+                addr: user.clone(),
+                old_staked: ONE,
+                new_staked: half,
+            },
+            StakeChangedHookMsg::StakeChangedHook {
+                addr: user.clone(),
+                old_staked: half,
+                new_staked: half,
+            }
+        ]);
+
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            contract.addr(),
+            &(ExecuteMsg::AddHook { address: failing_receiver.to_string() }),
+            &[]
+        ).unwrap();
+
+        let hooks: Vec<Addr> = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &(QueryMsg::ListHooks {}))
+            .unwrap();
+
+        assert_eq!(hooks, vec![receiver.clone(), failing_receiver.clone()]);
+
+        // a failing hook reverts the stake change that triggered it
+        let res = app.execute_contract(
+            user.clone(),
+            contract.addr(),
+            &(ExecuteMsg::Stake { lock_duration: None }),
+            &funds
+        );
+
+        assert!(res.is_err());
+
+        // but cannot block an emergency withdrawal
+        let res = app
+            .execute_contract(user.clone(), contract.addr(), &ExecuteMsg::EmergencyWithdraw {}, &[])
+            .unwrap();
+
+        assert!(
+            res.events
+                .iter()
+                .flat_map(|event| event.attributes.iter())
+                .any(|attribute| attribute.key == "hook_error")
+        );
+
+        let received: Vec<StakeChangedHookMsg> = app
+            .wrap()
+            .query_wasm_smart(receiver.clone(), &Empty {})
+            .unwrap();
+
+        assert_eq!(received.len(), 4);
+        assert_eq!(received[3], StakeChangedHookMsg::StakeChangedHook {
+            addr: user.clone(),
+            old_staked: half,
+            new_staked: Uint256::zero(),
+        });
+
+        let remove_hook = ExecuteMsg::RemoveHook { address: failing_receiver.to_string() };
+
+        app.execute_contract(Addr::unchecked(ADMIN), contract.addr(), &remove_hook, &[]).unwrap();
+
+        let res = app.execute_contract(Addr::unchecked(ADMIN), contract.addr(), &remove_hook, &[]);
+
+        expect_error(res, format!("Hook {} is not registered", failing_receiver));
+
+        let hooks: Vec<Addr> = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &(QueryMsg::ListHooks {}))
+            .unwrap();
+
+        assert_eq!(hooks, vec![receiver]);
+    }
+
+    #[test]
+    fn receipt_token_follows_transfers() {
+        let mut app = mock_app();
+        let cw_template_id = app.store_code(contract_template());
+        let cw20_id = app.store_code(cw20_contract_template());
+        let receipt_token_id = app.store_code(receipt_token_template());
+
+        let receipt_token_msg = |code_id: u64| InstantiateMsg {
+            receipt_token: Some(ReceiptTokenMsg {
+                code_id,
+                name: "Staked Injex".to_string(),
+                symbol: "sINJX".to_string(),
+                decimals: 18,
+            }),
+            ..instantiate_msg(INJEX_TOKEN)
+        };
+
+        // a CW20 that does not report transfers would let positions drift from their receipts
+        let res = app.instantiate_contract(
+            cw_template_id,
+            Addr::unchecked(ADMIN),
+            &receipt_token_msg(cw20_id),
+            &[],
+            "test",
+            None
+        );
+
+        assert_eq!(
+            res.unwrap_err().root_cause().to_string(),
+            "Receipt token code does not report transfers"
+        );
+
+        let contract = app
+            .instantiate_contract(
+                cw_template_id,
+                Addr::unchecked(ADMIN),
+                &receipt_token_msg(receipt_token_id),
+                &[],
+                "test",
+                None
+            )
+            .unwrap();
+
+        app.execute_contract(
+            Addr::unchecked(USER),
+            contract.clone(),
+            &ExecuteMsg::FundRewards {},
+            &[
+                Coin {
+                    denom: INJEX_TOKEN.to_string(),
+                    amount: Uint128::new(1000000000000000000000),
+                },
+            ]
+        ).unwrap();
+
+        let config: Config = app
+            .wrap()
+            .query_wasm_smart(contract.clone(), &QueryMsg::GetConfig {})
+            .unwrap();
+        let receipt_token = config.receipt_token.unwrap();
+        let receipt_token_info = app.wrap().query_wasm_contract_info(&receipt_token).unwrap();
+
+        assert_eq!(receipt_token_info.admin, Some(contract.to_string()));
+
+        let user = Addr::unchecked(USER);
+        let other = Addr::unchecked("inj12vpajtjf5cvmk2w737m0t8qwwkyjz0xgvxwyus");
+        let next_day = |app: &mut App| {
+            let block_info = app.block_info();
+
+            app.set_block(BlockInfo {
+                chain_id: block_info.chain_id,
+                height: block_info.height + 1,
+                time: block_info.time.plus_seconds(DAY),
+            });
+        };
+        let staker_info = |app: &App, user: &Addr| -> StakerInfo {
+            app.wrap()
+                .query_wasm_smart(contract.clone(), &(QueryMsg::GetStakerInfo { user: user.clone() }))
+                .unwrap()
+        };
+        let receipt_supply = |app: &App| -> Uint128 {
+            let info: TokenInfoResponse = app
+                .wrap()
+                .query_wasm_smart(receipt_token.clone(), &Cw20QueryMsg::TokenInfo {})
+                .unwrap();
+
+            info.total_supply
+        };
+
+        app.execute_contract(
+            user.clone(),
+            contract.clone(),
+            &(ExecuteMsg::Stake { lock_duration: None }),
+            &[
+                Coin {
+                    denom: INJEX_TOKEN.to_string(),
+                    amount: Uint128::try_from(ONE + ONE).unwrap(),
+                },
+            ]
+        ).unwrap();
+
+        assert_eq!(
+            query_cw20_balance(&app, &receipt_token, USER),
+            Uint128::try_from(ONE + ONE).unwrap()
+        );
+
+        let res = app.execute_contract(
+            user.clone(),
+            contract.clone(),
+            &(ExecuteMsg::Unstake { amount: ONE }),
+            &[]
+        );
+
+        expect_error(res, "Unstake by sending receipt tokens".to_string());
+
+        let res = app.execute_contract(
+            user.clone(),
+            contract.clone(),
+            &(ExecuteMsg::ReceiptTransfer {
+                from: other.to_string(),
+                to: user.to_string(),
+                amount: Uint128::try_from(ONE).unwrap(),
+            }),
+            &[]
+        );
+
+        expect_error(res, format!("Unauthorized sender {}", user));
+
+        next_day(&mut app);
+
+        app.execute_contract(
+            user.clone(),
+            receipt_token.clone(),
+            &(Cw20ExecuteMsg::Transfer {
+                recipient: other.to_string(),
+                amount: Uint128::try_from(ONE).unwrap(),
+            }),
+            &[]
+        ).unwrap();
+
+        // a day at 20% on 2 tokens stays with the sender, the stake moves with the tokens
+        let user_info = staker_info(&app, &user);
+        let other_info = staker_info(&app, &other);
+
+        assert_eq!(user_info.staked, ONE);
+        assert_eq!(user_info.rewards[0].reward, Uint256::from_u128(1095890410958904_u128));
+        assert_eq!(other_info.staked, ONE);
+        assert_eq!(other_info.rewards[0].reward, Uint256::zero());
+
+        next_day(&mut app);
+
+        let claimable: Vec<RewardAmount> = app
+            .wrap()
+            .query_wasm_smart(contract.clone(), &(QueryMsg::GetClaimableAmount { user: other.clone() }))
+            .unwrap();
+
+        assert_eq!(claimable[0].amount, Uint256::from_u128(547945205479452_u128));
+
+        app.execute_contract(
+            other.clone(),
+            receipt_token.clone(),
+            &(Cw20ExecuteMsg::Send {
+                contract: contract.to_string(),
+                amount: Uint128::try_from(ONE).unwrap(),
+                msg: to_json_binary(&ReceiveMsg::Unstake {}).unwrap(),
+            }),
+            &[]
+        ).unwrap();
+
+        assert_eq!(staker_info(&app, &other).staked, Uint256::zero());
+        assert_eq!(query_cw20_balance(&app, &receipt_token, other.as_str()), Uint128::zero());
+        assert_eq!(receipt_supply(&app), Uint128::try_from(ONE).unwrap());
+
+        let withdrawals: Vec<PendingWithdrawal> = app
+            .wrap()
+            .query_wasm_smart(
+                contract.clone(),
+                &(QueryMsg::GetPendingWithdrawals { user: other.clone() })
+            )
+            .unwrap();
+
+        assert_eq!(withdrawals[0].amount, ONE);
+
+        let half = ONE / Uint256::from_u128(2_u128);
+        let staking_caps = |max_total_staked: Option<Uint256>| ExecuteMsg::UpdateStakingCaps {
+            max_total_staked,
+            min_stake_amount: None,
+            max_stake_per_user: None,
+        };
+
+        app.send_tokens(user.clone(), other.clone(), &[
+            Coin {
+                denom: INJEX_TOKEN.to_string(),
+                amount: Uint128::try_from(ONE).unwrap(),
+            },
+        ]).unwrap();
+        app.execute_contract(
+            other.clone(),
+            contract.clone(),
+            &(ExecuteMsg::Stake { lock_duration: None }),
+            &[
+                Coin {
+                    denom: INJEX_TOKEN.to_string(),
+                    amount: Uint128::try_from(ONE).unwrap(),
+                },
+            ]
+        ).unwrap();
+
+        // transfers do not grow the pool, a cap lowered below it does not freeze them
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            contract.clone(),
+            &staking_caps(Some(half)),
+            &[]
+        ).unwrap();
+        app.execute_contract(
+            user.clone(),
+            receipt_token.clone(),
+            &(Cw20ExecuteMsg::Transfer {
+                recipient: other.to_string(),
+                amount: Uint128::try_from(half).unwrap(),
+            }),
+            &[]
+        ).unwrap();
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            contract.clone(),
+            &staking_caps(None),
+            &[]
+        ).unwrap();
+
+        assert_eq!(staker_info(&app, &user).staked, half);
+        assert_eq!(staker_info(&app, &other).staked, ONE + half);
+
+        // receipts transferred to the contract without a `Receive` would be stranded
+        let res = app.execute_contract(
+            user.clone(),
+            receipt_token.clone(),
+            &(Cw20ExecuteMsg::Transfer {
+                recipient: contract.to_string(),
+                amount: Uint128::try_from(half).unwrap(),
+            }),
+            &[]
+        );
+
+        assert_eq!(
+            res.unwrap_err().root_cause().to_string(),
+            "Receipts go back to the staking contract through Send"
+        );
+
+        // a spender sending the owner's receipts unstakes the owner's position
+        app.execute_contract(
+            user.clone(),
+            receipt_token.clone(),
+            &(Cw20ExecuteMsg::IncreaseAllowance {
+                spender: other.to_string(),
+                amount: Uint128::try_from(half).unwrap(),
+                expires: None,
+            }),
+            &[]
+        ).unwrap();
+        app.execute_contract(
+            other.clone(),
+            receipt_token.clone(),
+            &(Cw20ExecuteMsg::SendFrom {
+                owner: user.to_string(),
+                contract: contract.to_string(),
+                amount: Uint128::try_from(half).unwrap(),
+                msg: to_json_binary(&ReceiveMsg::Unstake {}).unwrap(),
+            }),
+            &[]
+        ).unwrap();
+
+        assert_eq!(staker_info(&app, &user).staked, Uint256::zero());
+        assert_eq!(staker_info(&app, &other).staked, ONE + half);
+        assert_eq!(
+            query_cw20_balance(&app, &receipt_token, other.as_str()),
+            Uint128::try_from(ONE + half).unwrap()
+        );
+
+        let withdrawals: Vec<PendingWithdrawal> = app
+            .wrap()
+            .query_wasm_smart(
+                contract.clone(),
+                &(QueryMsg::GetPendingWithdrawals { user: user.clone() })
+            )
+            .unwrap();
+
+        assert_eq!(withdrawals[0].amount, half);
+
+        // locking the position makes its tokens non-transferable
+        app.execute_contract(
+            user.clone(),
+            contract.clone(),
+            &(ExecuteMsg::Stake { lock_duration: Some(30 * DAY) }),
+            &[
+                Coin {
+                    denom: INJEX_TOKEN.to_string(),
+                    amount: Uint128::try_from(ONE).unwrap(),
+                },
+            ]
+        ).unwrap();
+
+        let res = app.execute_contract(
+            user.clone(),
+            receipt_token.clone(),
+            &(Cw20ExecuteMsg::Transfer {
+                recipient: other.to_string(),
+                amount: Uint128::one(),
+            }),
+            &[]
+        );
+
+        assert!(res.is_err());
+
+        // the stake and the rewards claimed into it are both backed by receipts
+        let staked = staker_info(&app, &user).staked;
+
+        let block_info = app.block_info();
+
+        app.set_block(BlockInfo {
+            chain_id: block_info.chain_id,
+            height: block_info.height + 1,
+            time: block_info.time.plus_seconds(30 * DAY),
+        });
+
+        assert_eq!(receipt_supply(&app), Uint128::try_from(staked + ONE + half).unwrap());
+
+        let res = app.execute_contract(
+            user.clone(),
+            contract.clone(),
+            &ExecuteMsg::EmergencyWithdraw {},
+            &[]
+        );
+
+        expect_error(res, format!("Send all {} receipt tokens to withdraw, got 0", staked));
+
+        app.execute_contract(
+            user.clone(),
+            receipt_token.clone(),
+            &(Cw20ExecuteMsg::Send {
+                contract: contract.to_string(),
+                amount: Uint128::try_from(staked).unwrap(),
+                msg: to_json_binary(&ReceiveMsg::EmergencyWithdraw {}).unwrap(),
+            }),
+            &[]
+        ).unwrap();
+
+        assert_eq!(staker_info(&app, &user).staked, Uint256::zero());
+        assert_eq!(receipt_supply(&app), Uint128::try_from(ONE + half).unwrap());
+    }
+
+    #[test]
+    fn simulate_and_project_rewards() {
+        let (mut app, contract) = proper_instantiate(true);
+
+        let denom = Denom::Native(INJEX_TOKEN.to_string());
+        let reward = |amount: Uint256| vec![RewardAmount { denom: denom.clone(), amount }];
+
+        stake_internal(&mut app, contract.clone(), ONE, true);
+
+        let simulation: SimulationResponse = app
+            .wrap()
+            .query_wasm_smart(
+                contract.addr(),
+                &(QueryMsg::SimulateStake {
+                    user: Addr::unchecked(USER),
+                    amount: ONE,
+                    lock_duration: None,
+                })
+            )
+            .unwrap();
+
+        // 20% of the doubled stake over the next year
+        assert_eq!(simulation, SimulationResponse {
+            staked: ONE * Uint256::from_u128(2_u128),
+            total_staked: ONE * Uint256::from_u128(2_u128),
+            rewards: reward(Uint256::zero()),
+            projected_yearly_rewards: reward(
+                (ONE * Uint256::from_u128(4_u128)) / Uint256::from_u128(10_u128)
+            ),
+            release_time: None,
+        });
+
+        let staker_info: StakerInfo = app
+            .wrap()
+            .query_wasm_smart(
+                contract.addr(),
+                &(QueryMsg::GetStakerInfo { user: Addr::unchecked(USER) })
+            )
+            .unwrap();
+
+        assert_eq!(staker_info.staked, ONE);
+
+        let block_info = app.block_info();
+        let now = block_info.time.plus_seconds(31_536_000);
+
+        app.set_block(BlockInfo {
+            chain_id: block_info.chain_id,
+            height: block_info.height + 1,
+            time: now,
+        });
+
+        let claimable: Vec<RewardAmount> = app
+            .wrap()
+            .query_wasm_smart(
+                contract.addr(),
+                &(QueryMsg::GetClaimableAmount { user: Addr::unchecked(USER) })
+            )
+            .unwrap();
+
+        assert_eq!(claimable, reward(ONE / Uint256::from_u128(5_u128)));
+
+        // times in the past project what is claimable now
+        let projected: Vec<RewardAmount> = app
+            .wrap()
+            .query_wasm_smart(
+                contract.addr(),
+                &(QueryMsg::ProjectRewards {
+                    user: Addr::unchecked(USER),
+                    at_time: Timestamp::from_seconds(0),
+                })
+            )
+            .unwrap();
+
+        assert_eq!(projected, claimable);
+
+        let projected: Vec<RewardAmount> = app
+            .wrap()
+            .query_wasm_smart(
+                contract.addr(),
+                &(QueryMsg::ProjectRewards {
+                    user: Addr::unchecked(USER),
+                    at_time: now.plus_seconds(31_536_000),
+                })
+            )
+            .unwrap();
+
+        assert_eq!(
+            projected,
+            reward((ONE * Uint256::from_u128(4_u128)) / Uint256::from_u128(10_u128))
+        );
+
+        let series: Vec<ProjectedRewards> = app
+            .wrap()
+            .query_wasm_smart(
+                contract.addr(),
+                &(QueryMsg::ProjectRewardsSeries {
+                    user: Addr::unchecked(USER),
+                    end_time: now.plus_seconds(31_536_000),
+                    points: 2,
+                })
+            )
+            .unwrap();
+
+        assert_eq!(series, vec![
+            ProjectedRewards {
+                time: now.plus_seconds(15_768_000),
+                rewards: reward((ONE * Uint256::from_u128(3_u128)) / Uint256::from_u128(10_u128)),
+            },
+            ProjectedRewards {
+                time: now.plus_seconds(31_536_000),
+                rewards: reward((ONE * Uint256::from_u128(4_u128)) / Uint256::from_u128(10_u128)),
+            }
+        ]);
+
+        let res: StdResult<SimulationResponse> = app
+            .wrap()
+            .query_wasm_smart(
+                contract.addr(),
+                &(QueryMsg::SimulateUnstake {
+                    user: Addr::unchecked(USER),
+                    amount: ONE * Uint256::from_u128(2_u128),
+                })
+            );
+
+        assert!(res.unwrap_err().to_string().contains("Insufficient balance"));
+
+        let simulation: SimulationResponse = app
+            .wrap()
+            .query_wasm_smart(
+                contract.addr(),
+                &(QueryMsg::SimulateUnstake { user: Addr::unchecked(USER), amount: ONE })
+            )
+            .unwrap();
+
+        assert_eq!(simulation, SimulationResponse {
+            staked: Uint256::zero(),
+            total_staked: Uint256::zero(),
+            rewards: reward(ONE / Uint256::from_u128(5_u128)),
+            projected_yearly_rewards: reward(Uint256::zero()),
+            release_time: Some(now.plus_seconds(UNBONDING_PERIOD)),
+        });
+
+        // nothing above was written
+        let state: State = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &(QueryMsg::GetState {}))
+            .unwrap();
+
+        assert_eq!(state.total_staked, ONE);
+        assert_eq!(state.reward_streams[0].ci_current, ONE);
+    }
+
+    #[test]
+    fn unknown_staker_queries_and_errors() {
+        let (mut app, contract) = proper_instantiate(true);
+
+        let stranger = Addr::unchecked("inj12vpajtjf5cvmk2w737m0t8qwwkyjz0xgvxwyus");
+
+        let staker_info: StakerInfo = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &(QueryMsg::GetStakerInfo { user: stranger.clone() }))
+            .unwrap();
+
+        assert_eq!(staker_info, StakerInfo {
+            staked: Uint256::zero(),
+            block_time: Timestamp::from_seconds(0),
+            multiplier: PERCENTS,
+            lock_end: Timestamp::from_seconds(0),
+            auto_compound: false,
+            rewards: vec![StakerReward {
+                ci_0: ONE,
+                reward: Uint256::zero(),
+            }],
+        });
+
+        let claimable: Vec<RewardAmount> = app
+            .wrap()
+            .query_wasm_smart(
+                contract.addr(),
+                &(QueryMsg::GetClaimableAmount { user: stranger.clone() })
+            )
+            .unwrap();
+
+        assert_eq!(claimable, vec![RewardAmount {
+            denom: Denom::Native(INJEX_TOKEN.to_string()),
+            amount: Uint256::zero(),
+        }]);
+
+        let res = app.execute_contract(
+            stranger.clone(),
+            contract.addr(),
+            &(ExecuteMsg::Unstake { amount: ONE }),
+            &[]
+        );
+
+        expect_error(res, "No tokens were staked".to_string());
+
+        let res = app.execute_contract(stranger.clone(), contract.addr(), &ExecuteMsg::Claim {}, &[]);
+
+        expect_error(res, "No claims".to_string());
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &(ExecuteMsg::ChangeApr {
+                denom: UncheckedDenom::Native(USDT.to_string()),
+                new_apr: Decimal256::percent(10),
+            }),
+            &[]
+        );
+
+        expect_error(res, "Reward stream USDT not found".to_string());
+    }
+
+    #[test]
+    fn accrual_overflow_is_an_error() {
+        let mut msg = instantiate_msg(INJEX_TOKEN);
+
+        msg.reward_mode = RewardMode::Emission;
+        msg.reward_streams[0].apr = Decimal256::zero();
+        msg.reward_streams[0].emission_rate = Uint256::MAX;
+
+        let (mut app, contract) = proper_instantiate_with_msg(msg, INJEX_TOKEN, true);
+
+        stake_internal(&mut app, contract.clone(), ONE, false);
+
+        let block_info = app.block_info();
+
+        app.set_block(BlockInfo {
+            chain_id: block_info.chain_id,
+            height: block_info.height + 1,
+            time: block_info.time.plus_seconds(1),
+        });
+
+        let res: StdResult<Vec<RewardAmount>> = app
+            .wrap()
+            .query_wasm_smart(
+                contract.addr(),
+                &(QueryMsg::GetClaimableAmount { user: Addr::unchecked(USER) })
+            );
+
+        assert!(res.unwrap_err().to_string().contains("Cannot Add with"));
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &ExecuteMsg::Claim {},
+            &[]
+        );
+
+        assert!(res.unwrap_err().root_cause().to_string().contains("Cannot Add with"));
+
+        // the principal still comes out, the rewards that cannot be computed are forfeited
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &ExecuteMsg::EmergencyWithdraw {},
+            &[]
+        );
+
+        assert!(res.is_ok());
+
+        let pending: Vec<PendingWithdrawal> = app
+            .wrap()
+            .query_wasm_smart(
+                contract.addr(),
+                &(QueryMsg::GetPendingWithdrawals { user: Addr::unchecked(USER) })
+            )
+            .unwrap();
+
+        assert_eq!(pending[0].amount, ONE);
+    }
+
+    #[test]
+    fn accrual_math_extreme_values() {
+        // 1.5 basis points over a year
+        let apr = Decimal256::from_atomics(15_u128, 5).unwrap();
+
+        assert_eq!(
+            simple_index(ONE, apr, 31_536_000).unwrap(),
+            Uint256::from_u128(1_000150000000000000_u128)
+        );
+
+        // a thousand idle years at 100%
+        assert_eq!(
+            simple_index(ONE, Decimal256::percent(100), 31_536_000_000).unwrap(),
+            ONE * Uint256::from_u128(1_001_u128)
+        );
+
+        // ten idle years compounding every second at 100%, (1 + 1 / 31536000)^315360000 is
+        // 22026.4622972..., every squaring rounds down
+        let index = compound_index(ONE, Decimal256::percent(100), 315_360_000).unwrap();
+
+        assert!(index > Uint256::from_u128(22026_462200000000000000_u128));
+        assert!(index < Uint256::from_u128(22026_462297222804315777_u128));
+
+        // the intermediate product no longer has to fit 256 bits, only the result
+        let index = Uint256::MAX / Uint256::from_u128(2_u128);
+
+        assert_eq!(
+            mul_div_floor(index, ONE * Uint256::from_u128(2_u128), ONE).unwrap(),
+            index * Uint256::from_u128(2_u128)
+        );
+
+        let weight = Uint256::from_u128(10_u128.pow(29)) * Uint256::from_u128(10_u128.pow(29));
+        let index = ONE + Uint256::from_u128(10_u128.pow(30));
+
+        assert_eq!(
+            accrued_reward(weight, index, ONE).unwrap(),
+            weight * Uint256::from_u128(10_u128.pow(12))
+        );
+
+        assert_eq!(
+            emission_index(ONE, Uint256::from_u128(10_u128.pow(30)), Uint256::one(), 31_536_000)
+                .unwrap(),
+            ONE + Uint256::from_u128(31_536_000_u128 * 10_u128.pow(30)) * ONE
+        );
+    }
+
+    #[test]
+    fn accrual_math_rounds_down() {
+        // the smallest representable APR over one second grows the index by less than a unit
+        assert_eq!(simple_index(ONE, Decimal256::new(Uint256::one()), 1).unwrap(), ONE);
+        assert_eq!(compound_index(ONE, Decimal256::new(Uint256::one()), 1).unwrap(), ONE);
+
+        let index = ONE + ONE / Uint256::from_u128(2_u128);
+
+        assert_eq!(accrued_reward(Uint256::from_u128(3_u128), index, ONE).unwrap(), Uint256::one());
+        assert_eq!(
+            compounded_reward(Uint256::from_u128(3_u128), Uint256::zero(), index, ONE).unwrap(),
+            Uint256::one()
+        );
+
+        // 1 token a second split between 3 weight units
+        assert_eq!(
+            emission_index(Uint256::zero(), Uint256::one(), Uint256::from_u128(3_u128), 1).unwrap(),
+            Uint256::from_u128(333333333333333333_u128)
+        );
+
+        assert_eq!(
+            mul_div_floor(Uint256::from_u128(5_u128), Uint256::from_u128(99_u128), 100_u128.into())
+                .unwrap(),
+            Uint256::from_u128(4_u128)
+        );
+    }
+
+    #[test]
+    fn accrual_math_errors() {
+        let err = compound_index(Uint256::MAX, Decimal256::percent(100), 31_536_000).unwrap_err();
+
+        assert!(err.to_string().contains("Cannot Mul with"));
+
+        let err = simple_index(Uint256::MAX, Decimal256::percent(100), 31_536_000).unwrap_err();
+
+        assert!(err.to_string().contains("Cannot Add with"));
+
+        let err = emission_index(ONE, Uint256::one(), Uint256::zero(), 1).unwrap_err();
+
+        assert!(err.to_string().contains("Cannot divide"));
+
+        let err = emission_index(Uint256::MAX, Uint256::one(), Uint256::one(), 1).unwrap_err();
+
+        assert!(err.to_string().contains("Cannot Add with"));
+
+        // an index can never move backwards
+        let err = accrued_reward(ONE, ONE, ONE + Uint256::one()).unwrap_err();
+
+        assert!(err.to_string().contains("Cannot Sub with"));
+    }
+
+    /// USER's rewards after a year at 20% while another staker claims `claims` times in between
+    fn yearly_reward_with_claims(accrual_mode: AccrualMode, claims: u64) -> Uint256 {
+        let mut msg = instantiate_msg(INJEX_TOKEN);
+
+        msg.accrual_mode = accrual_mode;
+
+        let (mut app, contract) = proper_instantiate_with_msg(msg, INJEX_TOKEN, true);
+
+        let other = Addr::unchecked("inj12vpajtjf5cvmk2w737m0t8qwwkyjz0xgvxwyus");
+        let funds = vec![Coin {
+            denom: INJEX_TOKEN.to_string(),
+            amount: Uint128::try_from(ONE).unwrap(),
+        }];
+
+        app.send_tokens(Addr::unchecked(USER), other.clone(), &funds).unwrap();
+
+        stake_internal(&mut app, contract.clone(), ONE, false);
+        app.execute_contract(
+            other.clone(),
+            contract.addr(),
+            &(ExecuteMsg::Stake { lock_duration: None }),
+            &funds
+        ).unwrap();
+
+        let step = 31_536_000 / (claims + 1);
+
+        for i in 0..=claims {
+            let block_info = app.block_info();
+            // the last step absorbs the remainder so every run covers exactly one year
+            let seconds = if i == claims { 31_536_000 - step * claims } else { step };
+
+            app.set_block(BlockInfo {
+                chain_id: block_info.chain_id,
+                height: block_info.height + 1,
+                time: block_info.time.plus_seconds(seconds),
+            });
+
+            if i < claims {
+                app.execute_contract(
+                    other.clone(),
+                    contract.addr(),
+                    &ExecuteMsg::Claim {},
+                    &[]
+                ).unwrap();
+            }
+        }
+
+        let claimable: Vec<RewardAmount> = app
+            .wrap()
+            .query_wasm_smart(
+                contract.addr(),
+                &(QueryMsg::GetClaimableAmount { user: Addr::unchecked(USER) })
+            )
+            .unwrap();
+
+        claimable[0].amount
+    }
+
+    #[test]
+    fn simple_accrual_ignores_interactions() {
+        let idle = yearly_reward_with_claims(AccrualMode::Simple, 0);
+        let busy = yearly_reward_with_claims(AccrualMode::Simple, 365);
+
+        // 20% of the principal, every settlement can round a unit away
+        assert_eq!(idle, ONE / Uint256::from_u128(5_u128));
+        assert!(idle - busy <= Uint256::from_u128(365_u128));
+    }
+
+    #[test]
+    fn compound_accrual_ignores_interactions() {
+        let idle = yearly_reward_with_claims(AccrualMode::Compound, 0);
+        let busy = yearly_reward_with_claims(AccrualMode::Compound, 365);
+
+        // (1 + 0.2 / 31536000)^31536000 - 1 at the rounded down per-second rate is
+        // 0.22140275735656030...
+        let reference = Uint256::from_u128(221402757356560302_u128);
+        let tolerance = Uint256::from_u128(1_000_000_000_u128);
+
+        assert!(idle <= reference && reference - idle <= tolerance);
+        assert!(busy <= reference && reference - busy <= tolerance);
+
+        // the claims only change how the rounding falls
+        assert!(idle.max(busy) - idle.min(busy) <= Uint256::from_u128(10_000_u128));
+
+        // compounding beats simple interest on the same rate
+        assert!(idle > yearly_reward_with_claims(AccrualMode::Simple, 0));
+    }
+
+    fn stake_cw20_internal(
+        app: &mut App,
+        contract: &CwTemplateContract,
+        token: &Addr,
+        stake_amount: Uint256
+    ) {
+        let msg = Cw20ExecuteMsg::Send {
+            contract: contract.addr().to_string(),
+            amount: Uint128::try_from(stake_amount).unwrap(),
+            msg: to_json_binary(&ReceiveMsg::Stake { lock_duration: None }).unwrap(),
+        };
+
+        let res = app.execute_contract(Addr::unchecked(USER), token.clone(), &msg, &[]);
+
+        assert!(res.is_ok());
     }
 
     fn stake_internal(
@@ -692,14 +4398,14 @@ mod tests {
         stake_amount: Uint256,
         check: bool
     ) -> (StakerInfo, State) {
-        let msg = ExecuteMsg::Stake {};
+        let msg = ExecuteMsg::Stake { lock_duration: None };
         let state_msg = QueryMsg::GetState {};
 
         let res = app.execute_contract(
             Addr::unchecked(USER),
             contract.addr(),
             &msg,
-            &vec![Coin {
+            &[Coin {
                 denom: INJEX_TOKEN.to_string(),
                 amount: Uint128::try_from(stake_amount).unwrap(),
             }]
@@ -724,14 +4430,14 @@ mod tests {
                 .query_wasm_smart(contract.addr(), &state_msg)
                 .unwrap();
 
-            assert_eq!(state.ci_current, state_before.ci_current);
+            assert_eq!(state.reward_streams[0].ci_current, state_before.reward_streams[0].ci_current);
             assert_eq!(state.ci_time_current, block_time);
             assert_eq!(state.total_staked, stake_amount);
 
             assert_eq!(user_staking.block_time, block_time);
-            assert_eq!(user_staking.ci_0, state.ci_current);
+            assert_eq!(user_staking.rewards[0].ci_0, state.reward_streams[0].ci_current);
             assert_eq!(user_staking.staked, stake_amount);
-            assert_eq!(user_staking.reward, Uint256::zero());
+            assert_eq!(user_staking.rewards[0].reward, Uint256::zero());
         }
 
         (user_staking, state)